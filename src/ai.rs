@@ -0,0 +1,188 @@
+use std::{path::PathBuf, time::Duration};
+
+use pareg::{ArgError, FromArg};
+
+use crate::{
+    args::Args,
+    board::Board,
+    book::Book,
+    clock::parse_duration,
+    engine::Engine,
+    err::Result,
+    mcts::Mcts,
+    piskvork::Piskvork,
+    rng::{self, Rng},
+    suit::Suit,
+    vec2::Vec2,
+};
+
+/// Algorithm used by computer player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AiKind {
+    /// Heuristic alpha-beta search ([`Engine`]) with its own time for each
+    /// move.
+    Minimax(Option<Duration>),
+    /// Monte Carlo tree search ([`Mcts`]) with its own number of playouts.
+    Mcts(Option<usize>),
+    /// External engine with the Piskvork protocol ([`Piskvork`]).
+    External(PathBuf),
+}
+
+/// Computer player.
+pub struct Ai {
+    kind: AiKind,
+    mcts: Mcts,
+    external: Option<Piskvork>,
+    book: Book,
+    rng: Rng,
+    /// Time limit for each move.
+    time: Duration,
+}
+
+impl Ai {
+    /// Creates the computer player. `playouts` and `time` are used if the
+    /// kind doesn't have its own. External engine is started immediately.
+    pub fn new(
+        kind: AiKind,
+        playouts: usize,
+        time: Duration,
+        seed: Option<u64>,
+        book: Book,
+    ) -> Result<Self> {
+        let rng =
+            |s: Option<u64>| s.map(Rng::new).unwrap_or_else(Rng::from_time);
+        let external = match &kind {
+            AiKind::External(path) => Some(Piskvork::new(path)?),
+            _ => None,
+        };
+        let playouts = match kind {
+            AiKind::Mcts(Some(p)) => p,
+            _ => playouts,
+        };
+        Ok(Self {
+            mcts: Mcts::new(playouts, rng(seed.map(rng::mix))),
+            kind,
+            external,
+            book,
+            rng: rng(seed),
+            time,
+        })
+    }
+
+    /// Creates the computer players for cross and circle as given by the
+    /// arguments.
+    pub fn from_args(args: &Args) -> Result<(Option<Self>, Option<Self>)> {
+        let x = args.ai(Suit::Cross);
+        let o = args.ai(Suit::Circle);
+        if x.is_none() && o.is_none() {
+            return Ok((None, None));
+        }
+
+        let book = Book::load(args.book())?;
+        let ai = |suit, kind| Self::for_player(args, suit, kind, book.clone());
+        Ok((
+            x.map(|k| ai(Suit::Cross, k)).transpose()?,
+            o.map(|k| ai(Suit::Circle, k)).transpose()?,
+        ))
+    }
+
+    /// Creates computer player of the given kind for `suit` with the
+    /// settings from the arguments.
+    pub fn for_player(
+        args: &Args,
+        suit: Suit,
+        kind: AiKind,
+        book: Book,
+    ) -> Result<Self> {
+        let seed = Self::player_seed(args.seed(), suit);
+        Self::new(kind, args.playouts(), args.move_time(), seed, book)
+    }
+
+    /// Gets the seed of the computer player of `suit`. Each computer player
+    /// has its own seed so that they don't play the same random games.
+    pub fn player_seed(seed: Option<u64>, suit: Suit) -> Option<u64> {
+        match suit {
+            Suit::Circle => seed.map(|s| s.wrapping_add(1)),
+            _ => seed,
+        }
+    }
+
+    /// Chooses move for the player on turn. Moves from the opening book are
+    /// preffered.
+    pub fn best_move(&mut self, board: &Board) -> Result<Option<Vec2>> {
+        if let Some(mov) = self.book.pick(board, &mut self.rng) {
+            return Ok(Some(mov));
+        }
+        match (&self.kind, &mut self.external) {
+            (AiKind::Minimax(time), _) => {
+                let time = time.unwrap_or(self.time).min(self.time);
+                Ok(Engine::best_move(board, time))
+            }
+            (AiKind::Mcts(_), _) => Ok(self.mcts.best_move(board, self.time)),
+            (AiKind::External(_), Some(e)) => {
+                if board.on_turn().is_none() {
+                    return Ok(None);
+                }
+                e.best_move(board, self.time).map(Some)
+            }
+            (AiKind::External(_), None) => Ok(None),
+        }
+    }
+}
+
+impl AiKind {
+    /// Gets short name of the computer player.
+    pub fn name(&self) -> String {
+        match self {
+            Self::Minimax(None) => "minimax".into(),
+            Self::Minimax(Some(t)) => format!("minimax:{}ms", t.as_millis()),
+            Self::Mcts(None) => "mcts".into(),
+            Self::Mcts(Some(p)) => format!("mcts:{p}"),
+            Self::External(path) => path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+}
+
+impl Default for AiKind {
+    fn default() -> Self {
+        Self::Minimax(None)
+    }
+}
+
+impl<'a> FromArg<'a> for AiKind {
+    fn from_arg(arg: &'a str) -> pareg::Result<Self> {
+        let (kind, param) = arg.split_once(':').unwrap_or((arg, ""));
+        let res = match (kind, param) {
+            ("easy", "") => Some(Self::Mcts(Some(1000))),
+            ("medium", "") => {
+                Some(Self::Minimax(Some(Duration::from_millis(200))))
+            }
+            ("hard", "") => Some(Self::Minimax(None)),
+            ("minimax", "") => Some(Self::Minimax(None)),
+            ("minimax", t) => {
+                parse_duration(t).map(|t| Self::Minimax(Some(t)))
+            }
+            ("mcts", "") => Some(Self::Mcts(None)),
+            ("mcts", p) => p
+                .parse()
+                .ok()
+                .filter(|p| *p != 0)
+                .map(|p| Self::Mcts(Some(p))),
+            ("ext", p) if !p.is_empty() => Some(Self::External(p.into())),
+            _ => None,
+        };
+        res.ok_or_else(|| ArgError::FailedToParse {
+            typ: "AI",
+            value: arg.to_owned().into(),
+            msg: Some(
+                "Expected `easy`, `medium`, `hard`, `minimax[:<time>]`, \
+                `mcts[:<playouts>]` or `ext:<engine path>`."
+                    .into(),
+            ),
+        })
+    }
+}
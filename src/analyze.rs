@@ -0,0 +1,80 @@
+use std::{path::Path, time::Duration};
+
+use termal::{formatmc, printmcln};
+
+use crate::{
+    board::Board, coord, err::Result, game::Game, suit::Suit, tss::Tss,
+    vec2::Vec2,
+};
+
+const VCF_TIME: Duration = Duration::from_millis(500);
+const VCT_TIME: Duration = Duration::from_millis(1000);
+
+/// Prints the forced wins found for both players after each move of the
+/// saved game.
+pub fn analyze(path: impl AsRef<Path>, color: bool) -> Result<()> {
+    let game = Game::load(path)?;
+    let mut board = game.board()?;
+
+    report(&board, color);
+    for (i, &mov) in game.moves().iter().enumerate() {
+        let suit = board.on_turn();
+        board.play_at(mov)?;
+        printmcln!(
+            color,
+            "{'gr}{:>3}.{'_} {} {'w}{}{'_}",
+            i + 1,
+            suit_name(suit, color),
+            coord::format(mov, board.size())
+        );
+
+        if board.wins_at(mov) {
+            printmcln!(color, "     {} wins.", suit_name(suit, color));
+            return Ok(());
+        }
+        report(&board, color);
+    }
+
+    Ok(())
+}
+
+fn report(board: &Board, color: bool) {
+    let on_turn = board.on_turn();
+    for suit in [on_turn, on_turn.oposite()] {
+        let mut board = board.clone();
+        board.set_on_turn(suit);
+
+        // VCT doesn't check all the defences, so it is only likely win.
+        let (kind, line, what) = if let Some(line) = Tss::vcf(&board, VCF_TIME)
+        {
+            ("VCF", line, "wins by")
+        } else if let Some(line) = Tss::vct(&board, VCT_TIME) {
+            ("VCT", line, "likely wins by")
+        } else {
+            continue;
+        };
+        let what = if suit == on_turn { what } else { "threatens" };
+        printmcln!(
+            color,
+            "     {} {what} {'y}{kind}{'_} in {}: {}",
+            suit_name(suit, color),
+            line.len().div_ceil(2),
+            format_line(&line, board.size())
+        );
+    }
+}
+
+fn format_line(line: &[Vec2], size: Vec2) -> String {
+    line.iter()
+        .map(|p| coord::format(*p, size))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn suit_name(suit: Suit, color: bool) -> String {
+    match suit {
+        Suit::Cross => formatmc!(color, "{'b}X{'_}"),
+        Suit::Circle => formatmc!(color, "{'r}O{'_}"),
+        Suit::None => "-".to_owned(),
+    }
+}
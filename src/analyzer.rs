@@ -0,0 +1,111 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use termal::formatc;
+
+use crate::{
+    board::Board,
+    engine::{Analysis, Engine},
+    suit::Suit,
+};
+
+const ANALYSIS_TIME: Duration = Duration::from_secs(30);
+const BAR_WIDTH: usize = 10;
+const BAR_SCALE: f64 = 256.;
+
+/// Evaluates positions on background thread.
+#[derive(Default)]
+pub struct Analyzer {
+    rx: Option<Receiver<Analysis>>,
+    stop: Arc<AtomicBool>,
+    on_turn: Suit,
+    last: Option<Analysis>,
+}
+
+impl Analyzer {
+    /// Starts analyzing the board. Any previous analysis is stopped.
+    pub fn start(&mut self, board: &Board) {
+        self.stop();
+        self.on_turn = board.on_turn();
+        if self.on_turn.is_none() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let board = board.clone();
+        self.rx = Some(rx);
+        self.stop = stop.clone();
+
+        thread::spawn(move || {
+            Engine::analyze(&board, ANALYSIS_TIME, Some(&stop), |a| {
+                _ = tx.send(a);
+            });
+        });
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.rx = None;
+        self.last = None;
+    }
+
+    /// Receives new results. Returns `true` if there are new results.
+    pub fn poll(&mut self) -> bool {
+        let Some(rx) = &self.rx else {
+            return false;
+        };
+
+        let last = rx.try_iter().last();
+        if last.is_some() {
+            self.last = last;
+        }
+        last.is_some()
+    }
+
+    pub fn format(&self) -> String {
+        let Some(a) = self.last else {
+            return formatc!("{'gr}Eval ...{'_}");
+        };
+
+        let winner = match a.win_in() {
+            Some(n) if n > 0 => Some((self.on_turn, n)),
+            Some(n) => Some((self.on_turn.oposite(), -n)),
+            None => None,
+        };
+        if let Some((winner, n)) = winner {
+            return match winner {
+                Suit::Circle => formatc!("{'r}O {'_}wins in {n}"),
+                _ => formatc!("{'b}X {'_}wins in {n}"),
+            };
+        }
+        if let Some(n) = a.likely_win {
+            return match self.on_turn {
+                Suit::Circle => formatc!("{'r}O {'_}likely wins in {n} (VCT)"),
+                _ => formatc!("{'b}X {'_}likely wins in {n} (VCT)"),
+            };
+        }
+
+        // Score from the view of cross
+        let score = if self.on_turn == Suit::Circle {
+            -a.score
+        } else {
+            a.score
+        };
+        let x_part = 1. / (1. + (-(score as f64) / BAR_SCALE).exp());
+        let x_len = ((x_part * BAR_WIDTH as f64).round() as usize)
+            .clamp(1, BAR_WIDTH - 1);
+        formatc!(
+            "{'b}X{}{'r}{}O{'_}",
+            "#".repeat(x_len),
+            "-".repeat(BAR_WIDTH - x_len)
+        )
+    }
+}
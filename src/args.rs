@@ -1,19 +1,80 @@
-use std::io::{self, IsTerminal};
+use std::{
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use pareg::{ArgError, ArgIterator, ByRef};
+use pareg::{ArgError, ArgIterator, ByRef, FromArg};
 use termal::raw;
 
-use crate::{err::Result, vec2::Vec2};
+use crate::{
+    ai::AiKind,
+    board_gui::labels_size,
+    clock::{self, TimeControl},
+    config::Config,
+    err::{Error, Result},
+    keymap::Keymap,
+    layout::Layout,
+    new_game::MAX_SIZE,
+    position::Position,
+    suit::Suit,
+    theme::Theme,
+    vec2::Vec2,
+};
+
+#[derive(Default, Clone, PartialEq, Eq)]
+pub enum Command {
+    #[default]
+    Play,
+    Stats,
+    Analyze(PathBuf),
+    BuildBook(PathBuf),
+    SelfPlay,
+    Tournament,
+    Puzzle,
+    BuildPuzzles(PathBuf),
+    Tutorial,
+}
 
 #[derive(Default)]
 pub struct Args {
+    command: Command,
     help: bool,
     size: Option<Vec2>,
     win_len: Option<usize>,
     use_color: Option<bool>,
+    time: Option<TimeControl>,
+    position: Option<Position>,
+    match_games: Option<usize>,
+    x_player: Option<String>,
+    o_player: Option<String>,
+    threats: Option<bool>,
+    x_ai: Option<AiKind>,
+    o_ai: Option<AiKind>,
+    playouts: Option<usize>,
+    seed: Option<u64>,
+    book: Option<PathBuf>,
+    delay: Option<Duration>,
+    move_time: Option<Duration>,
+    engines: Vec<AiKind>,
+    gauntlet: Option<bool>,
+    out_dir: Option<PathBuf>,
+    puzzles: Option<PathBuf>,
+    games: Option<usize>,
+    keymap: Keymap,
+    theme: Option<Theme>,
+    symbols: Option<(char, char)>,
+    unicode: Option<bool>,
+    layout: Option<Layout>,
+    labels: Option<bool>,
+    move_numbers: Option<bool>,
 }
 
 impl Args {
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
     pub fn help(&self) -> bool {
         self.help
     }
@@ -22,6 +83,117 @@ impl Args {
         self.size.unwrap_or((15, 15).into())
     }
 
+    pub fn match_games(&self) -> Option<usize> {
+        self.match_games
+    }
+
+    pub fn players(&self) -> Option<(&str, &str)> {
+        self.x_player.as_deref().zip(self.o_player.as_deref())
+    }
+
+    pub fn threats(&self) -> bool {
+        self.threats.unwrap_or_default()
+    }
+
+    /// Gets the kind of computer player that plays for `suit`.
+    pub fn ai(&self, suit: Suit) -> Option<AiKind> {
+        match suit {
+            Suit::Cross => self.x_ai.clone(),
+            Suit::Circle => self.o_ai.clone(),
+            Suit::None => None,
+        }
+    }
+
+    pub fn playouts(&self) -> usize {
+        self.playouts.unwrap_or(5000)
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Gets the time before the computer plays. By default, there is delay
+    /// only if the computer plays against itself.
+    pub fn delay(&self) -> Duration {
+        self.delay.unwrap_or_else(|| {
+            if self.x_ai.is_some() && self.o_ai.is_some() {
+                Duration::from_millis(500)
+            } else {
+                Duration::ZERO
+            }
+        })
+    }
+
+    /// Gets the time limit for each move of the computer.
+    pub fn move_time(&self) -> Duration {
+        self.move_time.unwrap_or(Duration::from_secs(1))
+    }
+
+    /// Gets the number of games that are played without the user interface.
+    pub fn games(&self) -> Option<usize> {
+        self.games
+    }
+
+    /// Gets the engines that play in tournament.
+    pub fn engines(&self) -> &[AiKind] {
+        &self.engines
+    }
+
+    pub fn gauntlet(&self) -> bool {
+        self.gauntlet.unwrap_or_default()
+    }
+
+    /// Gets the directory where the games played without the user
+    /// interface are saved.
+    pub fn out_dir(&self) -> Option<&Path> {
+        self.out_dir.as_deref()
+    }
+
+    pub fn puzzles(&self) -> Option<&Path> {
+        self.puzzles.as_deref()
+    }
+
+    pub fn book(&self) -> Option<&Path> {
+        self.book.as_deref()
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    /// Gets the theme with the symbols given by the user.
+    pub fn theme(&self) -> Theme {
+        let mut res = self.theme.unwrap_or_default();
+        if let Some((x, o)) = self.symbols {
+            res.set_symbols(x, o);
+        } else if self.unicode() {
+            res.set_symbols('✕', '○');
+        }
+        res
+    }
+
+    pub fn unicode(&self) -> bool {
+        self.unicode.unwrap_or_default()
+    }
+
+    pub fn labels(&self) -> bool {
+        self.labels.unwrap_or_default()
+    }
+
+    pub fn move_numbers(&self) -> bool {
+        self.move_numbers.unwrap_or_default()
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout.unwrap_or_default()
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+            .clone()
+            .unwrap_or_else(|| Position::empty(self.size()))
+    }
+
     pub fn win_len(&self) -> usize {
         self.win_len.unwrap_or_else(|| self.size().max().min(5))
     }
@@ -30,6 +202,10 @@ impl Args {
         self.use_color.unwrap_or(io::stdout().is_terminal())
     }
 
+    pub fn time(&self) -> Option<TimeControl> {
+        self.time
+    }
+
     pub fn parse<'a, I, A>(mut args: ArgIterator<'a, I>) -> Result<Self>
     where
         I: Iterator<Item = A>,
@@ -38,10 +214,30 @@ impl Args {
         let mut res = Self::default();
 
         args.next();
+        let args: Vec<_> = args.collect();
+
+        let config_path = args
+            .iter()
+            .skip_while(|a| **a != "--config")
+            .nth(1)
+            .map(|a| PathBuf::from(*a));
+        let config = Config::load(config_path.as_deref())?;
+        let mut defaults = Self::default();
+        for (line, args) in config.args() {
+            defaults
+                .parse_self(args.iter().into())
+                .map_err(|e| Error::InvalidConfig(line, e.to_string()))?;
+        }
+        for (line, action, keys) in config.keys() {
+            res.keymap
+                .bind_str(action, keys)
+                .map_err(|e| Error::InvalidConfig(line, e))?;
+        }
 
-        res.parse_self(args)?;
+        res.parse_self(args.iter().into())?;
+        res.merge(defaults);
 
-        res.finalize();
+        res.finalize()?;
         Ok(res)
     }
 
@@ -56,6 +252,8 @@ impl Args {
         while let Some(arg) = args.next() {
             match arg {
                 "-h" | "-?" | "--help" => self.help = true,
+                "stats" => self.command = Command::Stats,
+                "analyze" => self.command = Command::Analyze(args.next_arg()?),
                 "-s" | "--size" => {
                     let size: Vec2 =
                         args.next_key_val::<usize, usize>('x')?.into();
@@ -66,6 +264,19 @@ impl Args {
                             msg: Some("The size must not be 0.".into()),
                         })?;
                     }
+                    if size.max() > MAX_SIZE {
+                        Err(ArgError::FailedToParse {
+                            typ: "size",
+                            value: args.cur_arg::<&str>()?.to_owned().into(),
+                            msg: Some(
+                                format!(
+                                    "The size must be at most \
+                                    {MAX_SIZE}x{MAX_SIZE}."
+                                )
+                                .into(),
+                            ),
+                        })?;
+                    }
                     self.size = Some(size);
                 }
                 "-w" | "--win" | "--win-length" => {
@@ -83,6 +294,116 @@ impl Args {
                     self.use_color =
                         args.next_opt_bool("always", "never", "auto")?;
                 }
+                "-t" | "--time" => self.time = Some(args.next_arg()?),
+                "-m" | "--match" => {
+                    let games = args.next_arg()?;
+                    if games == 0 {
+                        Err(ArgError::FailedToParse {
+                            typ: "games",
+                            value: args.cur_arg::<&str>()?.to_owned().into(),
+                            msg: Some(
+                                "The match must have some games.".into(),
+                            ),
+                        })?;
+                    }
+                    self.match_games = Some(games);
+                }
+                "--x-player" => self.x_player = Some(Self::player(&mut args)?),
+                "--o-player" => self.o_player = Some(Self::player(&mut args)?),
+                "--threats" => self.threats = Some(true),
+                "--no-threats" => self.threats = Some(false),
+                "--ai" => {
+                    let (suit, kind) = Self::ai_arg(&mut args)?;
+                    match suit {
+                        Suit::Cross => self.x_ai = Some(kind),
+                        _ => self.o_ai = Some(kind),
+                    }
+                }
+                "--playouts" => {
+                    let playouts = args.next_arg()?;
+                    if playouts == 0 {
+                        Err(ArgError::FailedToParse {
+                            typ: "playouts",
+                            value: args.cur_arg::<&str>()?.to_owned().into(),
+                            msg: Some("There must be some playouts.".into()),
+                        })?;
+                    }
+                    self.playouts = Some(playouts);
+                }
+                "--seed" => self.seed = Some(args.next_arg()?),
+                "--book" => self.book = Some(args.next_arg()?),
+                "--delay" => self.delay = Some(Self::duration(&mut args)?),
+                "--move-time" => {
+                    self.move_time = Some(Self::duration(&mut args)?)
+                }
+                "selfplay" => self.command = Command::SelfPlay,
+                "tournament" => self.command = Command::Tournament,
+                "puzzle" => self.command = Command::Puzzle,
+                "build" if self.command == Command::Puzzle => {
+                    self.command = Command::BuildPuzzles(args.next_arg()?);
+                }
+                "tutorial" => self.command = Command::Tutorial,
+                "--puzzles" => self.puzzles = Some(args.next_arg()?),
+                "-e" | "--engine" => self.engines.push(args.next_arg()?),
+                "--gauntlet" => self.gauntlet = Some(true),
+                "--no-gauntlet" => self.gauntlet = Some(false),
+                "-o" | "--out" => self.out_dir = Some(args.next_arg()?),
+                "-n" | "--games" => {
+                    let games = args.next_arg()?;
+                    if games == 0 {
+                        Err(ArgError::FailedToParse {
+                            typ: "games",
+                            value: args.cur_arg::<&str>()?.to_owned().into(),
+                            msg: Some("There must be some games.".into()),
+                        })?;
+                    }
+                    self.games = Some(games);
+                }
+                "book" => {
+                    let sub: &str = args.next_arg()?;
+                    if sub != "build" {
+                        Err(ArgError::FailedToParse {
+                            typ: "book command",
+                            value: sub.to_owned().into(),
+                            msg: Some("Expected `build`.".into()),
+                        })?;
+                    }
+                    self.command = Command::BuildBook(args.next_arg()?);
+                }
+                // The config is loaded before the other flags are parsed.
+                "--config" => _ = args.next_arg::<&str>()?,
+                "--unicode" => self.unicode = Some(true),
+                "--no-unicode" => self.unicode = Some(false),
+                "--labels" => self.labels = Some(true),
+                "--no-labels" => self.labels = Some(false),
+                "--move-numbers" => self.move_numbers = Some(true),
+                "--no-move-numbers" => self.move_numbers = Some(false),
+                "--layout" => self.layout = Some(args.next_arg()?),
+                "--theme" => self.theme = Some(args.next_arg()?),
+                "--symbols" => {
+                    let arg: &str = args.next_arg()?;
+                    let mut chars = arg.chars();
+                    match (chars.next(), chars.next(), chars.next()) {
+                        (Some(x), Some(o), None)
+                            if x != o
+                                && !x.is_whitespace()
+                                && !o.is_whitespace() =>
+                        {
+                            self.symbols = Some((x, o))
+                        }
+                        _ => Err(ArgError::FailedToParse {
+                            typ: "symbols",
+                            value: arg.to_owned().into(),
+                            msg: Some(
+                                "Expected two different symbols for x and o."
+                                    .into(),
+                            ),
+                        })?,
+                    }
+                }
+                "-p" | "--position" => {
+                    self.position = Some(Position::load(args.next_arg()?)?);
+                }
                 _ => Err(ArgError::UnknownArgument(arg.to_owned().into()))?,
             }
         }
@@ -90,16 +411,219 @@ impl Args {
         Ok(())
     }
 
-    pub fn finalize(&mut self) {
+    /// Uses the values from `config` where the value wasn't given on the
+    /// command line. Size and position are taken only together so that the
+    /// values from the command line don't conflict with the config.
+    fn merge(&mut self, config: Self) {
+        if self.size.is_none() && self.position.is_none() {
+            self.size = config.size;
+            self.position = config.position;
+        }
+        self.win_len = self.win_len.or(config.win_len);
+        self.use_color = self.use_color.or(config.use_color);
+        self.time = self.time.or(config.time);
+        self.match_games = self.match_games.or(config.match_games);
+        self.x_player = self.x_player.take().or(config.x_player);
+        self.o_player = self.o_player.take().or(config.o_player);
+        self.threats = self.threats.or(config.threats);
+        self.x_ai = self.x_ai.take().or(config.x_ai);
+        self.o_ai = self.o_ai.take().or(config.o_ai);
+        self.playouts = self.playouts.or(config.playouts);
+        self.seed = self.seed.or(config.seed);
+        self.book = self.book.take().or(config.book);
+        self.delay = self.delay.or(config.delay);
+        self.move_time = self.move_time.or(config.move_time);
+        if self.engines.is_empty() {
+            self.engines = config.engines;
+        }
+        self.gauntlet = self.gauntlet.or(config.gauntlet);
+        self.out_dir = self.out_dir.take().or(config.out_dir);
+        self.puzzles = self.puzzles.take().or(config.puzzles);
+        self.games = self.games.or(config.games);
+        self.theme = self.theme.or(config.theme);
+        self.symbols = self.symbols.or(config.symbols);
+        self.unicode = self.unicode.or(config.unicode);
+        self.layout = self.layout.or(config.layout);
+        self.labels = self.labels.or(config.labels);
+        self.move_numbers = self.move_numbers.or(config.move_numbers);
+    }
+
+    pub fn finalize(&mut self) -> Result<()> {
+        if self.command == Command::Tournament && self.engines.len() < 2 {
+            Err(ArgError::FailedToParse {
+                typ: "engines",
+                value: self.engines.len().to_string().into(),
+                msg: Some(
+                    "Tournament needs at least two engines (`--engine`)."
+                        .into(),
+                ),
+            })?;
+        }
+
+        if let Some(name) = self.x_player.as_ref().xor(self.o_player.as_ref())
+        {
+            Err(ArgError::FailedToParse {
+                typ: "player",
+                value: name.clone().into(),
+                msg: Some(
+                    "Both players must be named to track statistics.".into(),
+                ),
+            })?;
+        }
+        if self.x_player.is_some() && self.x_player == self.o_player {
+            Err(ArgError::FailedToParse {
+                typ: "player",
+                value: self.x_player.clone().unwrap_or_default().into(),
+                msg: Some("The players must have different names.".into()),
+            })?;
+        }
+
+        if let Some(pos) = &self.position {
+            let size = pos.size();
+            if self.size.is_some_and(|s| s != size) {
+                Err(ArgError::FailedToParse {
+                    typ: "position",
+                    value: format!("{}x{}", size.x, size.y).into(),
+                    msg: Some(
+                        "The position size doesn't match the board size."
+                            .into(),
+                    ),
+                })?;
+            }
+            self.size = Some(size);
+        }
+
         if self.size.is_some() {
-            return;
+            return Ok(());
         }
 
         let Ok(size) = raw::term_size() else {
-            return;
+            return Ok(());
         };
 
-        let size: Vec2 = (size.char_width, size.char_height).into();
-        self.size = Some((size - (1, 2)).cdiv((4, 2)).cmax((1, 1)));
+        let space: Vec2 = (size.char_width, size.char_height).into();
+        let mut size = self.layout().cells(space);
+        if self.labels() {
+            // The board without the labels is larger, so its labels are
+            // large enough for the smaller board.
+            size =
+                self.layout().cells(space.saturating_sub(labels_size(size)));
+        }
+        self.size = Some(size);
+        Ok(())
+    }
+
+    fn player<'a, I, A>(args: &mut ArgIterator<'a, I>) -> Result<String>
+    where
+        I: Iterator<Item = A>,
+        A: ByRef<&'a str>,
+    {
+        let name: &str = args.next_arg()?;
+        if name.is_empty() || name.contains(['\t', '\n', '\r']) {
+            Err(ArgError::FailedToParse {
+                typ: "player",
+                value: name.to_owned().into(),
+                msg: Some(
+                    "The name must not be empty or contain tabs or newlines."
+                        .into(),
+                ),
+            })?;
+        }
+        Ok(name.to_owned())
+    }
+
+    fn ai_arg<'a, I, A>(
+        args: &mut ArgIterator<'a, I>,
+    ) -> Result<(Suit, AiKind)>
+    where
+        I: Iterator<Item = A>,
+        A: ByRef<&'a str>,
+    {
+        let arg: &str = args.next_arg()?;
+        let (player, kind) = arg.split_once(':').unwrap_or((arg, "minimax"));
+        let suit = match player {
+            "x" | "X" => Suit::Cross,
+            "o" | "O" => Suit::Circle,
+            _ => Err(ArgError::FailedToParse {
+                typ: "player",
+                value: arg.to_owned().into(),
+                msg: Some(
+                    "Expected `x` or `o` optionally followed by `:<ai>`."
+                        .into(),
+                ),
+            })?,
+        };
+        Ok((suit, AiKind::from_arg(kind)?))
+    }
+
+    fn duration<'a, I, A>(args: &mut ArgIterator<'a, I>) -> Result<Duration>
+    where
+        I: Iterator<Item = A>,
+        A: ByRef<&'a str>,
+    {
+        let arg: &str = args.next_arg()?;
+        let res = clock::parse_duration(arg).ok_or_else(|| {
+            ArgError::FailedToParse {
+                typ: "duration",
+                value: arg.to_owned().into(),
+                msg: Some("Expected e.g. `2s` or `200ms`.".into()),
+            }
+        })?;
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    fn parse(config: &str, args: &[&str]) -> Result<Args> {
+        // Tests run in parallel, so each config has its own file.
+        static CNT: AtomicUsize = AtomicUsize::new(0);
+        let path = env::temp_dir().join(format!(
+            "ttoe-test-{}-{}.ini",
+            std::process::id(),
+            CNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, config).unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let args: Vec<_> = ["ttoe", "--config", &path]
+            .iter()
+            .chain(args)
+            .map(|a| a.to_string())
+            .collect();
+        let res = Args::parse(args.iter().into());
+        _ = fs::remove_file(&path);
+        res
+    }
+
+    #[test]
+    fn test_config_precedence() {
+        let config = "size = 19x19\nthreats = true\nunicode = true\nwin = 4";
+        let args =
+            parse(config, &["-p", "o:...../..x..", "--no-threats"]).unwrap();
+        assert_eq!(args.size(), Vec2::new(5, 2));
+        assert_eq!(args.win_len(), 4);
+        assert!(!args.threats());
+        assert!(args.unicode());
+
+        let args = parse(config, &["-s", "9x9", "-w", "5"]).unwrap();
+        assert_eq!(args.size(), Vec2::new(9, 9));
+        assert_eq!(args.win_len(), 5);
+        assert!(args.threats());
+    }
+
+    #[test]
+    fn test_config_false() {
+        let args = parse("labels = false", &["--labels"]).unwrap();
+        assert!(args.labels());
+        let args = parse("labels = true", &["--no-labels"]).unwrap();
+        assert!(!args.labels());
+        assert!(parse("size = false", &[]).is_err());
     }
 }
@@ -1,35 +1,50 @@
-use std::ops::{Index, IndexMut, RangeBounds};
+use std::ops::{Index, RangeBounds};
 
 use crate::{
     err::{Error, Result},
+    position::Position,
     slice_2d::Slice2d,
     suit::Suit,
     vec2::Vec2,
+    zobrist::{turn_key, Zobrist},
 };
 
+#[derive(Clone)]
 pub struct Board {
     board: Vec<Suit>,
     size: Vec2,
     on_turn: Suit,
     win_len: usize,
     selected: Vec2,
-    last: Vec2,
+    moves: Vec<Vec2>,
     win_pos: Option<(Vec2, Vec2<isize>)>,
+    start: Position,
+    zobrist: Zobrist,
 }
 
 impl Board {
-    pub fn new(size: impl Into<Vec2>, win_len: usize) -> Self {
-        let mut board = Vec::new();
-        let size = size.into();
-        board.resize_with(size.prod(), || Suit::None);
-        Self {
-            board,
+    pub fn new(start: Position, win_len: usize) -> Result<Self> {
+        let size = start.size();
+        if win_len == 0 || win_len > size.max() {
+            return Err(Error::WinLength(win_len, size.x, size.y));
+        }
+
+        let mut res = Self {
+            zobrist: Zobrist::new(start.cells(), size),
+            board: start.cells().to_vec(),
             size,
-            on_turn: Suit::Cross,
+            on_turn: start.on_turn(),
             win_len,
             selected: (size - (1, 1)) / 2,
-            last: (0, 0).into(),
+            moves: vec![],
             win_pos: None,
+            start,
+        };
+
+        match res.check_win() {
+            Some(Suit::None) => Ok(res),
+            Some(_) => Err(Error::PositionWon),
+            None => Err(Error::PositionFull),
         }
     }
 
@@ -41,6 +56,10 @@ impl Board {
         self.on_turn
     }
 
+    pub fn set_on_turn(&mut self, suit: Suit) {
+        self.on_turn = suit;
+    }
+
     pub fn inspect_mode(&mut self) {
         self.on_turn = Suit::None
     }
@@ -61,24 +80,76 @@ impl Board {
         self.win_len
     }
 
+    /// Gets the moves played from the starting position.
+    pub fn moves(&self) -> &[Vec2] {
+        &self.moves
+    }
+
+    pub fn start(&self) -> &Position {
+        &self.start
+    }
+
     pub fn play(&mut self) -> Result<()> {
+        self.play_at(self.selected)
+    }
+
+    pub fn play_at(&mut self, pos: Vec2) -> Result<()> {
         if self.on_turn == Suit::None {
             return Ok(());
         }
 
-        let sel = self.selected;
-
-        if self[sel] != Suit::None {
+        if self[pos] != Suit::None {
             return Err(Error::AlreadyPopulated);
         }
 
-        self.last = sel;
-        self[sel] = self.on_turn;
+        self.moves.push(pos);
+        self.set(pos, self.on_turn);
         self.on_turn = self.on_turn.oposite();
 
         Ok(())
     }
 
+    /// Checks whether the stone at `pos` is part of winning line.
+    pub fn wins_at(&self, pos: Vec2) -> bool {
+        !self[pos].is_none()
+            && [(1, 0), (0, 1), (1, 1), (-1, 1)]
+                .into_iter()
+                .any(|d| self.line_len(pos, d) >= self.win_len)
+    }
+
+    /// Gets the number of same stones in line through `pos` in direction
+    /// `dir` (both ways).
+    pub fn line_len(&self, pos: Vec2, dir: impl Into<Vec2<isize>>) -> usize {
+        let dir = dir.into();
+        let suit = self[pos];
+        let mut len = 1;
+        for dir in [dir, -dir] {
+            let mut p = pos.wrapping_add_signed(dir);
+            while self.contains(p) && self[p] == suit {
+                len += 1;
+                p = p.wrapping_add_signed(dir);
+            }
+        }
+        len
+    }
+
+    /// Gets the cells where the player on turn may play.
+    pub fn legal_moves(&self) -> impl Iterator<Item = Vec2> + '_ {
+        Vec2::new(0, 0).to(self.size).filter(|p| self[*p].is_none())
+    }
+
+    pub fn empty_count(&self) -> usize {
+        self.board.iter().filter(|s| s.is_none()).count()
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        pos.lt_and(self.size)
+    }
+
+    pub fn contains_signed(&self, pos: Vec2<isize>) -> bool {
+        pos.x >= 0 && pos.y >= 0 && self.contains(pos.unsigned())
+    }
+
     pub fn check_win(&mut self) -> Option<Suit> {
         let mut draw = true;
         for pos in Vec2::new(0, 0).to(self.size) {
@@ -111,21 +182,42 @@ impl Board {
     }
 
     pub fn reset(&mut self) {
-        for v in &mut self.board {
-            *v = Suit::None;
-        }
-        self.on_turn = Suit::Cross;
+        self.reset_with(self.start.on_turn());
+    }
+
+    /// Resets the board to the starting position with `on_turn` starting.
+    /// The starting position is updated so that the game is saved with the
+    /// right starting player.
+    pub fn reset_with(&mut self, on_turn: Suit) {
+        self.start.set_on_turn(on_turn);
+        self.board.copy_from_slice(self.start.cells());
+        self.zobrist = Zobrist::new(&self.board, self.size);
+        self.on_turn = on_turn;
+        self.moves.clear();
         self.win_pos = None;
     }
 
+    /// Takes back the last move. The player of the move is on turn again
+    /// even if the game has already ended.
     pub fn undo(&mut self) {
-        let last = self.last;
-        if self[last] != Suit::None {
-            self[last] = Suit::None;
-            self.on_turn = self.on_turn.oposite();
+        if let Some(last) = self.moves.pop() {
+            self.on_turn = self[last];
+            self.set(last, Suit::None);
+            self.win_pos = None;
         }
     }
 
+    /// Gets Zobrist hash of the position including the player on turn.
+    pub fn hash(&self) -> u64 {
+        self.zobrist.hash() ^ turn_key(self.on_turn)
+    }
+
+    /// Gets hash of the position that is the same for all its rotations and
+    /// reflections.
+    pub fn canonical_hash(&self) -> u64 {
+        self.zobrist.canonical(self.size) ^ turn_key(self.on_turn)
+    }
+
     pub fn slice(&self, range: impl RangeBounds<Vec2>) -> Slice2d<'_, Suit> {
         let start = match range.start_bound() {
             std::ops::Bound::Included(s) => *s,
@@ -142,6 +234,14 @@ impl Board {
         Slice2d::new(&self.board, self.size, start, end - start)
     }
 
+    /// Sets the cell and updates the hash.
+    fn set(&mut self, pos: Vec2, suit: Suit) {
+        let idx = pos.y * self.size.x + pos.x;
+        self.zobrist.toggle(pos, self.board[idx], self.size);
+        self.zobrist.toggle(pos, suit, self.size);
+        self.board[idx] = suit;
+    }
+
     fn is_win(
         &mut self,
         pos: impl Into<Vec2<usize>>,
@@ -177,15 +277,37 @@ where
     }
 }
 
-impl<T> IndexMut<T> for Board
-where
-    T: Into<Vec2>,
-{
-    fn index_mut(&mut self, idx: T) -> &mut Self::Output {
-        let idx = idx.into();
-        if idx.gt_or(self.size) {
-            panic!("Index {idx} out of range of {}", self.size);
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare the hashes first because it is fast.
+        self.hash() == other.hash()
+            && self.size == other.size
+            && self.on_turn == other.on_turn
+            && self.board == other.board
+    }
+}
+
+impl Eq for Board {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_after_win() {
+        let mut board = Board::new(Position::empty((5, 5)), 3).unwrap();
+        for pos in [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)] {
+            board.play_at(pos.into()).unwrap();
         }
-        &mut self.board[idx.y * self.size.x + idx.x]
+        assert_eq!(board.check_win(), Some(Suit::Cross));
+        board.inspect_mode();
+
+        board.undo();
+        assert_eq!(board.on_turn(), Suit::Cross);
+        assert_eq!(board[(2, 0)], Suit::None);
+        assert_eq!(board.win_pos(), None);
+        board.undo();
+        assert_eq!(board.on_turn(), Suit::Circle);
+        assert_eq!(board.moves().len(), 3);
     }
 }
@@ -1,16 +1,83 @@
 use termal::{codes, formatc, term_text::TermText};
 
 use crate::{
-    board::Board, draw_buffer::DrawBuffer, slice_2d::Slice2d, suit::Suit,
+    board::Board,
+    coord,
+    draw_buffer::DrawBuffer,
+    layout::Layout,
+    slice_2d::Slice2d,
+    suit::Suit,
+    theme::{Cursor, Theme},
+    threats::{Threat, Threats},
     vec2::Vec2,
 };
 
 #[derive(Default)]
 pub struct GuiState {
     view_pos: Option<Vec2>,
+    threats: bool,
+    theme: Theme,
+    unicode: bool,
+    layout: Layout,
+    labels: bool,
+    move_numbers: bool,
+    /// Position that will be in the center of the view.
+    focus: Option<Vec2>,
 }
 
 impl GuiState {
+    pub fn threats(&self) -> bool {
+        self.threats
+    }
+
+    pub fn set_threats(&mut self, show: bool) {
+        self.threats = show;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Draws the grid with unicode box drawing characters instead of
+    /// ascii.
+    pub fn set_unicode(&mut self, unicode: bool) {
+        self.unicode = unicode;
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn labels(&self) -> bool {
+        self.labels
+    }
+
+    /// Shows the coordinates of rows and columns around the board.
+    pub fn set_labels(&mut self, show: bool) {
+        self.labels = show;
+    }
+
+    pub fn move_numbers(&self) -> bool {
+        self.move_numbers
+    }
+
+    /// Shows the order of the moves instead of the symbols.
+    pub fn set_move_numbers(&mut self, show: bool) {
+        self.move_numbers = show;
+    }
+
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    fn charset(&self) -> &'static Charset {
+        if self.unicode {
+            &UNICODE
+        } else {
+            &ASCII
+        }
+    }
+
     pub fn scroll_by(&mut self, off: impl Into<Vec2<isize>>) {
         if let Some(ref mut pos) = self.view_pos {
             *pos = pos.saturating_add_signed(off.into());
@@ -20,6 +87,11 @@ impl GuiState {
     pub fn center(&mut self) {
         self.view_pos = None;
     }
+
+    /// Scrolls so that `pos` is in the center of the view.
+    pub fn focus(&mut self, pos: Vec2) {
+        self.focus = Some(pos);
+    }
 }
 
 struct WinCrossPath {
@@ -45,10 +117,58 @@ impl WinCrossPath {
     }
 }
 
-const UR_CROSS: WinCrossPath = WinCrossPath::new(',', '\'', '/', (0, 2));
-const DR_CROSS: WinCrossPath = WinCrossPath::new('\'', ',', '\\', (4, 2));
-const D_CROSS: WinCrossPath = WinCrossPath::new(' ', ' ', '|', (2, 2));
-const R_CROSS: WinCrossPath = WinCrossPath::new('-', '-', '-', (4, 1));
+/// Characters used to draw the grid, the winning line and the cursor.
+struct Charset {
+    horizontal: char,
+    vertical: char,
+    /// Crossings of the grid lines. The first index is the row (top, middle
+    /// and bottom) and the second is the column (left, middle and right).
+    crossings: [[char; 3]; 3],
+    ur_cross: WinCrossPath,
+    dr_cross: WinCrossPath,
+    d_cross: WinCrossPath,
+    r_cross: WinCrossPath,
+    /// Corners of the cursor. If [`None`], the mark of the player on turn
+    /// is used.
+    cursor_corners: Option<[char; 4]>,
+    cursor_horizontal: char,
+    cursor_vertical: char,
+    /// Empty cell in the layout without grid.
+    empty: char,
+    /// Mark of cell where the player can make open four. It must differ
+    /// from `empty`.
+    open_four: char,
+}
+
+const ASCII: Charset = Charset {
+    horizontal: '-',
+    vertical: '|',
+    crossings: [['+'; 3]; 3],
+    ur_cross: WinCrossPath::new(',', '\'', '/', (0, 2)),
+    dr_cross: WinCrossPath::new('\'', ',', '\\', (4, 2)),
+    d_cross: WinCrossPath::new(' ', ' ', '|', (2, 2)),
+    r_cross: WinCrossPath::new('-', '-', '-', (4, 1)),
+    cursor_corners: None,
+    cursor_horizontal: '-',
+    cursor_vertical: '|',
+    empty: '.',
+    open_four: '*',
+};
+
+const UNICODE: Charset = Charset {
+    horizontal: '─',
+    vertical: '│',
+    crossings: [['┌', '┬', '┐'], ['├', '┼', '┤'], ['└', '┴', '┘']],
+    ur_cross: WinCrossPath::new(',', '\'', '╱', (0, 2)),
+    dr_cross: WinCrossPath::new('\'', ',', '╲', (4, 2)),
+    d_cross: WinCrossPath::new(' ', ' ', '│', (2, 2)),
+    r_cross: WinCrossPath::new('─', '─', '─', (4, 1)),
+    cursor_corners: Some(['┏', '┓', '┗', '┛']),
+    cursor_horizontal: '━',
+    cursor_vertical: '┃',
+    empty: '·',
+    open_four: '•',
+};
 
 const SCROLLOFF: Vec2 = Vec2::new(2, 2);
 
@@ -60,7 +180,8 @@ impl Board {
         space: Vec2,
         msg: &str,
     ) {
-        let draw_size = self.size().cmul((4, 2)) + (1, 2);
+        let labels = self.labels_size(gui);
+        let draw_size = gui.layout.draw_size(self.size()) + (0, 1) + labels;
         if draw_size.gt_or(space) {
             self.draw_scrollable(gui, out, space, msg);
             return;
@@ -70,14 +191,24 @@ impl Board {
         out.add_base(Self::center(space, draw_size - (0, 1)));
 
         let board = self.slice(..);
+        if gui.labels {
+            self.draw_labels(board, out, gui, labels);
+        }
+        out.add_base(labels);
 
-        Self::draw_grid(board, out);
+        self.draw_grid(board, out, gui);
+        self.draw_moves(board, out, gui);
+
+        if gui.threats {
+            self.draw_threats(board, out, gui);
+        }
 
         if let Some(pd) = self.win_pos() {
-            self.draw_win_cross(board, out, pd);
+            self.draw_win_cross(board, out, pd, gui);
         }
 
         gui.view_pos = None;
+        gui.focus = None;
 
         self.draw_selected(gui, out);
     }
@@ -95,15 +226,24 @@ impl Board {
         // ..+---+..
         //   :   :
         //    msg
-        let view_size = (space - (5, 4)).cdiv((4, 2)).cmin(self.size());
+        let layout = gui.layout;
+        let labels = self.labels_size(gui);
+        let margin = layout.draw_size((0, 0).into()) + labels + (4, 3);
+        let view_size = space
+            .saturating_sub(margin)
+            .cdiv(layout.cell())
+            .cmin(self.size());
         if view_size.lt_or((1, 1)) {
             Self::draw_no_space(out);
             return;
         }
 
-        let view_pos = gui
-            .view_pos
-            .unwrap_or_else(|| Self::center(self.size(), view_size));
+        let view_pos = match gui.focus.take() {
+            Some(pos) => pos.saturating_sub(view_size / 2),
+            None => gui
+                .view_pos
+                .unwrap_or_else(|| Self::center(self.size(), view_size)),
+        };
 
         let bot_right = (view_pos
             .cmin(self.selected().saturating_sub(SCROLLOFF))
@@ -112,26 +252,71 @@ impl Board {
             .cmax((self.selected() + (1, 1) + SCROLLOFF).cmin(self.size()));
         let top_left = bot_right - view_size;
 
-        Self::draw_msg(out, space, view_size.cmul((4, 2)) + (4, 3), msg);
+        let draw_size = layout.draw_size(view_size);
+        Self::draw_msg(out, space, draw_size + (4, 2) + labels, msg);
 
         let board = self.slice(top_left..bot_right);
         out.add_base(
-            Self::center(space - (0, 1), board.size().cmul((4, 2)) + (1, 1))
-                - (2, 1),
+            Self::center(space - (0, 1) - labels, draw_size) - (2, 1),
         );
-        self.draw_elipsis(board, out);
+        if gui.labels {
+            self.draw_labels(board, out, gui, labels + (2, 1));
+        }
+        out.add_base(labels);
+        self.draw_elipsis(board, out, gui);
 
         out.add_base((2, 1));
-        Self::draw_grid(board, out);
+        self.draw_grid(board, out, gui);
+        self.draw_moves(board, out, gui);
+
+        if gui.threats {
+            self.draw_threats(board, out, gui);
+        }
 
         if let Some(pd) = self.win_pos() {
-            self.draw_win_cross(board, out, pd);
+            self.draw_win_cross(board, out, pd, gui);
         }
 
         gui.view_pos = Some(top_left);
         self.draw_selected(gui, out);
     }
 
+    /// Gets the space taken by the labels on the left and above the board.
+    fn labels_size(&self, gui: &GuiState) -> Vec2 {
+        if gui.labels {
+            labels_size(self.size())
+        } else {
+            (0, 0).into()
+        }
+    }
+
+    /// Draws the column letters above the board and the row numbers left of
+    /// the board. `grid` is the position of the grid relative to the labels.
+    fn draw_labels(
+        &self,
+        board: Slice2d<Suit>,
+        out: &mut DrawBuffer,
+        gui: &GuiState,
+        grid: Vec2,
+    ) {
+        let layout = gui.layout;
+        let cell = layout.cell();
+        let sym = layout.symbol() + grid;
+        let width = self.labels_size(gui).x - 1;
+
+        *out += codes::RESET;
+        *out += gui.theme.grid();
+        for x in 0..board.size().x {
+            let name = coord::column_name(board.start().x + x);
+            out.move_to((sym.x + x * cell.x + 1 - name.len(), 0));
+            *out += name;
+        }
+        for y in 0..board.size().y {
+            out.move_to((0, sym.y + y * cell.y));
+            *out += format!("{:>width$}", self.size().y - board.start().y - y);
+        }
+    }
+
     fn draw_no_space(out: &mut DrawBuffer) {
         out.move_to((0, 0));
         *out += "Not enough space.";
@@ -158,57 +343,260 @@ impl Board {
         *out += formatc!("{'_}{msg}");
     }
 
-    fn draw_grid(board: Slice2d<'_, Suit>, out: &mut DrawBuffer) {
-        *out += formatc!("{'_ gr}");
+    fn draw_grid(
+        &self,
+        board: Slice2d<'_, Suit>,
+        out: &mut DrawBuffer,
+        gui: &GuiState,
+    ) {
+        match gui.layout {
+            Layout::Full => self.draw_full_grid(board, out, gui),
+            Layout::Wide => self.draw_wide_grid(board, out, gui),
+            Layout::Compact => Self::draw_compact_grid(board, out, gui),
+        }
+    }
+
+    fn draw_full_grid(
+        &self,
+        board: Slice2d<'_, Suit>,
+        out: &mut DrawBuffer,
+        gui: &GuiState,
+    ) {
+        let chars = gui.charset();
+        let rows = self.size().y + 1;
+        *out += codes::RESET;
+        *out += gui.theme.grid();
         for y in 0..board.size().y {
             out.move_to((0, y * 2));
-            out.repeat(board.size().x, "+---");
-            *out += '+';
+            let row = edge(board.start().y + y, rows);
+            self.draw_grid_line(board, out, chars, row);
 
             out.move_to((0, y * 2 + 1));
             for x in 0..board.size().x {
-                *out += "| ";
-                Self::draw_suit(out, board[(x, y)]);
-                *out += formatc!(" {'gr}");
+                *out += chars.vertical;
+                *out += ' ';
+                Self::draw_suit(out, &gui.theme, board[(x, y)]);
+                *out += ' ';
+                *out += gui.theme.grid();
             }
-            *out += '|';
+            *out += chars.vertical;
         }
 
         out.move_to((0, board.size().y * 2));
-        out.repeat(board.size().x, "+---");
-        *out += '+';
+        let row = edge(board.end().y, rows);
+        self.draw_grid_line(board, out, chars, row);
     }
 
-    fn draw_elipsis(&self, board: Slice2d<Suit>, out: &mut DrawBuffer) {
-        *out += formatc!("{'_ gr}");
+    fn draw_grid_line(
+        &self,
+        board: Slice2d<'_, Suit>,
+        out: &mut DrawBuffer,
+        chars: &Charset,
+        row: usize,
+    ) {
+        let cols = self.size().x + 1;
+        for x in board.start().x..=board.end().x {
+            if x != board.start().x {
+                out.repeat(3, chars.horizontal);
+            }
+            *out += chars.crossings[row][edge(x, cols)];
+        }
+    }
+
+    fn draw_wide_grid(
+        &self,
+        board: Slice2d<'_, Suit>,
+        out: &mut DrawBuffer,
+        gui: &GuiState,
+    ) {
+        //  +--+--+-
+        //  +--X--+-
+        let chars = gui.charset();
+        let size = self.size();
+        *out += codes::RESET;
+        for y in 0..board.size().y {
+            out.move_to((0, y));
+            let row = edge(board.start().y + y, size.y);
+            for x in 0..board.size().x {
+                let col = edge(board.start().x + x, size.x);
+                let (pre, post) = match col {
+                    _ if size.x == 1 => (' ', ' '),
+                    0 => (' ', chars.horizontal),
+                    2 => (chars.horizontal, ' '),
+                    _ => (chars.horizontal, chars.horizontal),
+                };
+
+                *out += gui.theme.grid();
+                *out += pre;
+                match board[(x, y)] {
+                    Suit::None => *out += chars.crossings[row][col],
+                    s => Self::draw_suit(out, &gui.theme, s),
+                }
+                *out += gui.theme.grid();
+                *out += post;
+            }
+        }
+    }
+
+    fn draw_compact_grid(
+        board: Slice2d<'_, Suit>,
+        out: &mut DrawBuffer,
+        gui: &GuiState,
+    ) {
+        //  . . X .
+        //  . O . .
+        let chars = gui.charset();
+        *out += codes::RESET;
+        for y in 0..board.size().y {
+            out.move_to((0, y));
+            for x in 0..board.size().x {
+                *out += ' ';
+                match board[(x, y)] {
+                    Suit::None => {
+                        *out += gui.theme.grid();
+                        *out += chars.empty;
+                    }
+                    s => Self::draw_suit(out, &gui.theme, s),
+                }
+            }
+            *out += ' ';
+        }
+    }
+
+    /// Highlights the last move and draws the move numbers if enabled. Only
+    /// the last digits of the number are shown if it doesn't fit the cell.
+    fn draw_moves(
+        &self,
+        board: Slice2d<'_, Suit>,
+        out: &mut DrawBuffer,
+        gui: &GuiState,
+    ) {
+        let layout = gui.layout;
+        let moves = self.moves();
+        let first = if gui.move_numbers {
+            0
+        } else {
+            moves.len().saturating_sub(1)
+        };
+        for (i, pos) in moves.iter().enumerate().skip(first) {
+            if !board.contains(*pos) {
+                continue;
+            }
+
+            let last = i + 1 == moves.len();
+            let cell =
+                (*pos - board.start()).cmul(layout.cell()) + layout.symbol();
+            if last {
+                *out += codes::BOLD;
+                *out += codes::UNDERLINE;
+            }
+            if gui.move_numbers {
+                let digits = layout.digits();
+                let max = 10_usize.pow(digits as u32);
+                // Truncated numbers are padded so that `105` is `05` and not
+                // `5` which could be mistaken for the fifth move.
+                let num = if i + 1 >= max {
+                    format!("{:0digits$}", (i + 1) % max)
+                } else {
+                    (i + 1).to_string()
+                };
+                // Compact cells have space only before the symbol.
+                let start = if layout == Layout::Compact {
+                    cell.x + 1 - num.len()
+                } else {
+                    cell.x - (num.len() - 1) / 2
+                };
+                out.move_to((start, cell.y));
+                *out += gui.theme.color(self[*pos]);
+                *out += num;
+            } else {
+                out.move_to(cell);
+                Self::draw_suit(out, &gui.theme, self[*pos]);
+            }
+            if last {
+                *out += codes::RESET_BOLD;
+                *out += codes::RESET_UNDERLINE;
+            }
+        }
+    }
+
+    fn draw_threats(
+        &self,
+        board: Slice2d<'_, Suit>,
+        out: &mut DrawBuffer,
+        gui: &GuiState,
+    ) {
+        let layout = gui.layout;
+        let chars = gui.charset();
+        let threats = Threats::new(self);
+        for pos in Vec2::new(0, 0).to(board.size()) {
+            let (suit, threat) =
+                threats.strongest(pos + board.start(), self.on_turn());
+            let chr = match threat {
+                Threat::None => continue,
+                Threat::OpenFour => chars.open_four,
+                Threat::Win => '!',
+            };
+            out.move_to(pos.cmul(layout.cell()) + layout.symbol());
+            *out += gui.theme.color(suit);
+            *out += chr;
+        }
+    }
+
+    fn draw_elipsis(
+        &self,
+        board: Slice2d<Suit>,
+        out: &mut DrawBuffer,
+        gui: &GuiState,
+    ) {
+        *out += codes::RESET;
+        *out += gui.theme.grid();
+
+        // With grid, the elipsis is at the grid lines. Otherwise it is at
+        // the cells.
+        let layout = gui.layout;
+        let lines = |cnt: usize, cell: usize, sym: usize| {
+            if layout == Layout::Full {
+                (0..=cnt).map(|i| i * cell).collect::<Vec<_>>()
+            } else {
+                (0..cnt).map(|i| i * cell + sym).collect()
+            }
+        };
+        let cols = lines(board.size().x, layout.cell().x, layout.symbol().x);
+        let rows = lines(board.size().y, layout.cell().y, layout.symbol().y);
+        let draw_size = layout.draw_size(board.size());
 
         let chr = if board.start().y != 0 { ':' } else { ' ' };
-        out.move_to((2, 0));
-        out.repeat(board.size().x, format!("{chr}   "));
-        *out += chr;
+        for x in &cols {
+            out.move_to((x + 2, 0));
+            *out += chr;
+        }
 
         let s = if board.start().x != 0 { ".." } else { "  " };
-        out.move_to((0, 1));
-        out.repeat(board.size().y, formatc!("{s}\n\n{'ml ml}"));
-        *out += s;
+        for y in &rows {
+            out.move_to((0, y + 1));
+            *out += s;
+        }
 
         let s = if board.end().x != self.size().x {
             ".."
         } else {
             "  "
         };
-        out.move_to((3 + board.size().x * 4, 1));
-        out.repeat(board.size().y, formatc!("{s}\n\n{'ml ml}"));
-        *out += s;
+        for y in &rows {
+            out.move_to((draw_size.x + 2, y + 1));
+            *out += s;
+        }
 
         let chr = if board.end().y != self.size().y {
             ':'
         } else {
             ' '
         };
-        out.move_to((2, 2 + board.size().y * 2));
-        out.repeat(board.size().x, format!("{chr}   "));
-        *out += chr;
+        for x in &cols {
+            out.move_to((x + 2, draw_size.y + 1));
+            *out += chr;
+        }
     }
 
     fn draw_win_cross(
@@ -216,15 +604,22 @@ impl Board {
         board: Slice2d<'_, Suit>,
         out: &mut DrawBuffer,
         pd: (Vec2, Vec2<isize>),
+        gui: &GuiState,
     ) {
+        if gui.layout != Layout::Full {
+            self.draw_win_cells(board, out, pd, gui);
+            return;
+        }
+
+        let chars = gui.charset();
         let path = match pd.1.tuple() {
-            (-1, 1) => &UR_CROSS,
-            (1, 1) => &DR_CROSS,
-            (0, 1) => &D_CROSS,
-            (1, 0) => &R_CROSS,
+            (-1, 1) => &chars.ur_cross,
+            (1, 1) => &chars.dr_cross,
+            (0, 1) => &chars.d_cross,
+            (1, 0) => &chars.r_cross,
             _ => return,
         };
-        self.draw_cell_cross(board, out, pd, path);
+        self.draw_cell_cross(board, out, pd, path, &gui.theme);
     }
 
     fn draw_cell_cross(
@@ -233,9 +628,9 @@ impl Board {
         out: &mut DrawBuffer,
         (mut pos, dir): (Vec2, Vec2<isize>),
         path: &WinCrossPath,
+        theme: &Theme,
     ) {
-        let (color, _) = Self::get_color_char(self[pos]);
-        *out += color;
+        *out += theme.color(self[pos]);
 
         for i in (0..self.win_len()).rev() {
             if board.contains(pos) {
@@ -255,37 +650,84 @@ impl Board {
         }
     }
 
+    /// Highlights the winning cells in the layouts without grid.
+    fn draw_win_cells(
+        &self,
+        board: Slice2d<'_, Suit>,
+        out: &mut DrawBuffer,
+        (mut pos, dir): (Vec2, Vec2<isize>),
+        gui: &GuiState,
+    ) {
+        let layout = gui.layout;
+        let suit = self[pos];
+        for _ in 0..self.win_len() {
+            if board.contains(pos) {
+                out.move_to(
+                    (pos - board.start()).cmul(layout.cell())
+                        + layout.symbol(),
+                );
+                *out += codes::INVERSE;
+                Self::draw_suit(out, &gui.theme, suit);
+                *out += codes::RESET_INVERSE;
+            }
+            pos = pos.wrapping_add_signed(dir);
+        }
+    }
+
     fn draw_selected(&self, gui: &mut GuiState, out: &mut DrawBuffer) {
-        let pos =
-            (self.selected() - gui.view_pos.unwrap_or_default()).cmul((4, 2));
+        let layout = gui.layout;
+        let pos = (self.selected() - gui.view_pos.unwrap_or_default())
+            .cmul(layout.cell());
+
+        let chars = gui.charset();
+        let color = gui.theme.color(self.on_turn());
+        if layout != Layout::Full {
+            // Brackets around the symbol.
+            out.move_to(pos + layout.symbol() - (1, 0));
+            *out += formatc!("{color}[{'mr}]");
+            return;
+        }
 
-        out.move_to(pos);
-        let (color, chr) = Self::get_color_char(self.on_turn());
+        let mark = gui.theme.mark(self.on_turn());
+        let [tl, tr, bl, br] = chars.cursor_corners.unwrap_or([mark; 4]);
+        let h = chars.cursor_horizontal;
+        let v = chars.cursor_vertical;
 
-        *out += format!("{color}{chr}---{chr}");
+        out.move_to(pos);
+        *out += format!("{color}{tl}{h}{h}{h}{tr}");
         out.move_to(pos + (0, 1));
-        *out += formatc!("|{'mr mr mr}|");
+        match gui.theme.cursor() {
+            Cursor::Marks => *out += formatc!("{v}{'mr mr mr}{v}"),
+            Cursor::Brackets => *out += formatc!("{v}[{'mr}]{v}"),
+        }
         out.move_to(pos + (0, 2));
-        *out += format!("{chr}---{chr}");
+        *out += format!("{bl}{h}{h}{h}{br}");
     }
 
-    fn draw_suit(out: &mut DrawBuffer, suit: Suit) {
-        match suit {
-            Suit::None => *out += " ",
-            Suit::Cross => *out += formatc!("{'b}X"),
-            Suit::Circle => *out += formatc!("{'r}O"),
-        }
+    fn draw_suit(out: &mut DrawBuffer, theme: &Theme, suit: Suit) {
+        *out += theme.color(suit);
+        *out += theme.symbol(suit);
     }
 
     fn center(available: impl Into<Vec2>, required: impl Into<Vec2>) -> Vec2 {
         available.into().saturating_sub(required.into()) / 2
     }
+}
 
-    fn get_color_char(suit: Suit) -> (&'static str, char) {
-        match suit {
-            Suit::Circle => (codes::RED_FG, 'o'),
-            Suit::Cross => (codes::BLUE_FG, 'x'),
-            Suit::None => (codes::WHITE_FG, '#'),
-        }
+/// Gets the index of the crossing at `pos` on line of `len` crossings: `0`
+/// at the start, `2` at the end and `1` otherwise.
+fn edge(pos: usize, len: usize) -> usize {
+    if pos == 0 {
+        0
+    } else if pos + 1 >= len {
+        2
+    } else {
+        1
     }
 }
+
+/// Gets the space taken by the coordinates left of and above board of the
+/// given size.
+pub fn labels_size(size: Vec2) -> Vec2 {
+    (size.y.to_string().len() + 1, 1).into()
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use termal::printmcln;
+
+use crate::{
+    board::Board,
+    coord, dirs,
+    err::{Error, Result},
+    game::Game,
+    position::Position,
+    rng::Rng,
+    suit::Suit,
+    vec2::Vec2,
+    zobrist,
+};
+
+const BOOK_FILE: &str = "book.tsv";
+/// Number of moves from the start of each game that are added to the book.
+const BOOK_PLIES: usize = 12;
+
+/// Opening book with recommended moves for positions. Each line of the file
+/// has the tab separated fields: win length, position, move and weight.
+///
+/// The position is in the canonical text form: it has the same format as
+/// `--position` with rows separated by `/` and the player on turn always
+/// given. From all the rotations and reflections of the position, the one
+/// with the smallest text is used. The move is in the gomoku notation on the
+/// canonical position.
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    /// Moves with weights by win length and canonical position.
+    entries: HashMap<(usize, String), Vec<(Vec2, usize)>>,
+}
+
+impl Book {
+    /// Loads the book from `path` or from the data directory. Missing book
+    /// or data directory gives empty book, so that the book is optional.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(p) => p.to_owned(),
+            None => match Self::file() {
+                Err(Error::NoDataDir) => return Ok(Self::default()),
+                p => p?,
+            },
+        };
+        let s = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut res = Self::default();
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (win_len, pos, mov, weight) =
+                parse_line(line).ok_or(Error::InvalidBook(i + 1))?;
+            res.add(win_len, pos, mov, weight);
+        }
+        Ok(res)
+    }
+
+    /// Saves the book to `path` or to the data directory.
+    pub fn save(&self, path: Option<&Path>) -> Result<PathBuf> {
+        let path = match path {
+            Some(p) => p.to_owned(),
+            None => Self::file()?,
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut s = String::new();
+        for ((win_len, pos), moves) in entries {
+            let size = text_size(pos);
+            for (mov, weight) in moves {
+                s += &format!(
+                    "{win_len}\t{pos}\t{}\t{weight}\n",
+                    coord::format(*mov, size)
+                );
+            }
+        }
+        fs::write(&path, s)?;
+        Ok(path)
+    }
+
+    /// Builds the book from all the saved games in `dir`. Moves of the
+    /// winner have double weight and moves of the loser are not used.
+    pub fn build(dir: impl AsRef<Path>, color: bool) -> Result<Self> {
+        let mut files: Vec<_> = fs::read_dir(dir)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<std::io::Result<_>>()?;
+        files.sort();
+
+        let mut res = Self::default();
+        for file in files.iter().filter(|f| f.is_file()) {
+            if let Err(e) = res.add_game(file) {
+                printmcln!(
+                    color,
+                    "{'y}warning: {'_}skipping {}: {e}",
+                    file.display()
+                );
+            }
+        }
+        Ok(res)
+    }
+
+    /// Gets the number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Picks random move for the position from the book. Moves with larger
+    /// weight are more likely.
+    pub fn pick(&self, board: &Board, rng: &mut Rng) -> Option<Vec2> {
+        if board.on_turn().is_none() {
+            return None;
+        }
+
+        let (pos, sym) = canonical(board);
+        let moves = self.entries.get(&(board.win_len(), pos))?;
+        let moves: Vec<_> = moves
+            .iter()
+            .map(|(m, w)| (zobrist::untransform(sym, *m, board.size()), *w))
+            .filter(|(m, _)| board.contains(*m) && board[*m].is_none())
+            .collect();
+
+        let total: usize = moves.iter().map(|(_, w)| w).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut n = rng.below(total);
+        for (mov, weight) in moves {
+            if n < weight {
+                return Some(mov);
+            }
+            n -= weight;
+        }
+        None
+    }
+
+    fn add_game(&mut self, file: &Path) -> Result<()> {
+        let game = Game::load(file)?;
+        let mut board = game.board()?;
+
+        // Find the winner first.
+        let mut winner = Suit::None;
+        for &mov in game.moves() {
+            let suit = board.on_turn();
+            board.play_at(mov)?;
+            if board.wins_at(mov) {
+                winner = suit;
+                break;
+            }
+        }
+
+        board.reset();
+        for &mov in game.moves().iter().take(BOOK_PLIES) {
+            let suit = board.on_turn();
+            let weight = match winner {
+                Suit::None => 1,
+                w if w == suit => 2,
+                _ => 0,
+            };
+            if weight != 0 {
+                let (pos, sym) = canonical(&board);
+                let mov = zobrist::transform(sym, mov, board.size());
+                self.add(board.win_len(), pos, mov, weight);
+            }
+            board.play_at(mov)?;
+            if board.wins_at(mov) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add(&mut self, win_len: usize, pos: String, mov: Vec2, weight: usize) {
+        let moves = self.entries.entry((win_len, pos)).or_default();
+        match moves.iter_mut().find(|(m, _)| *m == mov) {
+            Some((_, w)) => *w += weight,
+            None => moves.push((mov, weight)),
+        }
+    }
+
+    fn file() -> Result<PathBuf> {
+        Ok(dirs::data_dir().ok_or(Error::NoDataDir)?.join(BOOK_FILE))
+    }
+}
+
+/// Gets the canonical text form of the position on the board and the
+/// symmetry that transforms the board to it.
+fn canonical(board: &Board) -> (String, usize) {
+    let size = board.size();
+    let mut cells = vec![Suit::None; size.prod()];
+    (0..zobrist::symmetries(size))
+        .map(|sym| {
+            for pos in Vec2::new(0, 0).to(size) {
+                let p = zobrist::transform(sym, pos, size);
+                cells[p.y * size.x + p.x] = board[pos];
+            }
+            (position_text(&cells, size, board.on_turn()), sym)
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn position_text(cells: &[Suit], size: Vec2, on_turn: Suit) -> String {
+    let mut res = match on_turn {
+        Suit::Circle => "o:".to_owned(),
+        _ => "x:".to_owned(),
+    };
+    for (i, row) in cells.chunks(size.x).enumerate() {
+        if i != 0 {
+            res.push('/');
+        }
+        res.extend(row.iter().map(|c| match c {
+            Suit::None => '.',
+            Suit::Cross => 'x',
+            Suit::Circle => 'o',
+        }));
+    }
+    res
+}
+
+/// Gets the size of position in the canonical text form.
+fn text_size(pos: &str) -> Vec2 {
+    let rows = pos.get(2..).unwrap_or_default();
+    let width = rows.split('/').next().unwrap_or_default().len();
+    (width, rows.split('/').count()).into()
+}
+
+fn parse_line(line: &str) -> Option<(usize, String, Vec2, usize)> {
+    let mut fields = line.split('\t');
+    let win_len = fields.next()?.parse().ok()?;
+    let pos = fields.next()?.to_owned();
+    let size = Position::parse(&pos).ok()?.size();
+    let mov = coord::parse(fields.next()?, size)?;
+    let weight = fields.next()?.parse().ok()?;
+    fields
+        .next()
+        .is_none()
+        .then_some((win_len, pos, mov, weight))
+}
@@ -0,0 +1,337 @@
+use std::time::{Duration, Instant};
+
+use pareg::{ArgError, FromArg};
+use termal::{codes, formatc};
+
+use crate::suit::Suit;
+
+#[derive(Debug, Clone, Copy)]
+enum Overtime {
+    None,
+    Increment(Duration),
+    ByoYomi { periods: usize, period: Duration },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimeControl {
+    main: Duration,
+    overtime: Overtime,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PlayerClock {
+    left: Duration,
+    periods: usize,
+    in_overtime: bool,
+}
+
+pub struct Clock {
+    control: TimeControl,
+    cross: PlayerClock,
+    circle: PlayerClock,
+    running: Option<(Suit, Instant)>,
+    /// Clocks of the players just before the increment or the new period
+    /// for each move. They are restored on undo.
+    history: Vec<(Suit, PlayerClock)>,
+}
+
+impl TimeControl {
+    fn parse(s: &str) -> Option<Self> {
+        let (main, overtime) = match s.split_once('+') {
+            Some((main, over)) => (main, Some(over)),
+            None => (s, None),
+        };
+
+        let main = parse_duration(main)?;
+        let overtime = match overtime {
+            None => Overtime::None,
+            Some(o) => match o.split_once('x') {
+                Some((periods, period)) => Overtime::ByoYomi {
+                    periods: periods.parse().ok()?,
+                    period: parse_duration(period)?,
+                },
+                None => Overtime::Increment(parse_duration(o)?),
+            },
+        };
+
+        if let Overtime::ByoYomi { periods, period } = overtime {
+            if periods == 0 || period.is_zero() {
+                return None;
+            }
+        }
+
+        // Without main time, only byo-yomi gives time for the first move.
+        (!main.is_zero() || matches!(overtime, Overtime::ByoYomi { .. }))
+            .then_some(Self { main, overtime })
+    }
+}
+
+impl<'a> FromArg<'a> for TimeControl {
+    fn from_arg(arg: &'a str) -> pareg::Result<Self> {
+        Self::parse(arg).ok_or_else(|| ArgError::FailedToParse {
+            typ: "time control",
+            value: arg.to_owned().into(),
+            msg: Some(
+                "Expected `<time>[+<increment>]` or \
+                `<time>+<periods>x<period>`, e.g. `5m+3s` or `10m+5x30s`."
+                    .into(),
+            ),
+        })
+    }
+}
+
+impl PlayerClock {
+    fn new(control: &TimeControl) -> Self {
+        let mut res = Self {
+            left: control.main,
+            periods: 0,
+            in_overtime: false,
+        };
+        if let Overtime::ByoYomi { periods, .. } = control.overtime {
+            res.periods = periods;
+        }
+        res
+    }
+
+    /// Returns `false` if the flag has fallen.
+    fn spend(&mut self, control: &TimeControl, mut time: Duration) -> bool {
+        loop {
+            if time < self.left {
+                self.left -= time;
+                return true;
+            }
+
+            time -= self.left;
+            self.left = Duration::ZERO;
+
+            let Overtime::ByoYomi { period, .. } = control.overtime else {
+                return false;
+            };
+            if self.periods == 0 {
+                return false;
+            }
+
+            self.periods -= 1;
+            self.left = period;
+            self.in_overtime = true;
+        }
+    }
+
+    fn moved(&mut self, control: &TimeControl) {
+        match control.overtime {
+            Overtime::None => {}
+            Overtime::Increment(inc) => self.left += inc,
+            Overtime::ByoYomi { period, .. } => {
+                if self.in_overtime {
+                    self.left = period;
+                }
+            }
+        }
+    }
+
+    fn format(&self) -> String {
+        let secs = self.left.as_secs();
+        let mut res = if secs >= 3600 {
+            format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+        } else {
+            format!("{}:{:02}", secs / 60, secs % 60)
+        };
+        if self.in_overtime || self.periods != 0 {
+            res += &format!(" ({})", self.periods + self.in_overtime as usize);
+        }
+        res
+    }
+}
+
+impl Clock {
+    pub fn new(control: TimeControl, on_turn: Suit) -> Self {
+        let mut res = Self {
+            cross: PlayerClock::new(&control),
+            circle: PlayerClock::new(&control),
+            control,
+            running: None,
+            history: vec![],
+        };
+        res.start(on_turn);
+        res
+    }
+
+    pub fn reset(&mut self, on_turn: Suit) {
+        *self = Self::new(self.control, on_turn);
+    }
+
+    /// Stops the clock of the player on turn and starts the clock of `next`.
+    pub fn switch(&mut self, next: Suit) {
+        if let Some(suit) = self.charge() {
+            let control = self.control;
+            self.history.push((suit, *self.player(suit)));
+            self.player_mut(suit).moved(&control);
+        }
+        self.start(next);
+    }
+
+    /// Gives the clock to `next` after undo. The time spent by the player
+    /// that was on turn is not returned and the clock of the player of the
+    /// undone move is as it was just before the move, so the increment or
+    /// the new byo-yomi period is taken back.
+    pub fn undo(&mut self, next: Suit) {
+        self.charge();
+        if let Some((suit, clock)) = self.history.pop() {
+            *self.player_mut(suit) = clock;
+        }
+        self.start(next);
+    }
+
+    pub fn stop(&mut self) {
+        self.charge();
+    }
+
+    /// Returns the player whose flag has fallen.
+    pub fn flagged(&self) -> Option<Suit> {
+        let (suit, start) = self.running?;
+        let mut player = *self.player(suit);
+        (!player.spend(&self.control, start.elapsed())).then_some(suit)
+    }
+
+    pub fn format(&self) -> String {
+        let mut cross = self.cross;
+        let mut circle = self.circle;
+        if let Some((suit, start)) = self.running {
+            let player = match suit {
+                Suit::Circle => &mut circle,
+                _ => &mut cross,
+            };
+            player.spend(&self.control, start.elapsed());
+        }
+
+        let (x_col, o_col) = match self.running {
+            Some((Suit::Cross, _)) => ("", codes::GRAY_FG),
+            Some((Suit::Circle, _)) => (codes::GRAY_FG, ""),
+            _ => ("", ""),
+        };
+
+        formatc!(
+            "{'b}X {'_}{x_col}{} {'r}O {'_}{o_col}{}{'_}",
+            cross.format(),
+            circle.format()
+        )
+    }
+
+    fn start(&mut self, suit: Suit) {
+        self.running = (!suit.is_none()).then(|| (suit, Instant::now()));
+    }
+
+    fn charge(&mut self) -> Option<Suit> {
+        let (suit, start) = self.running.take()?;
+        let control = self.control;
+        self.player_mut(suit).spend(&control, start.elapsed());
+        Some(suit)
+    }
+
+    fn player(&self, suit: Suit) -> &PlayerClock {
+        match suit {
+            Suit::Circle => &self.circle,
+            _ => &self.cross,
+        }
+    }
+
+    fn player_mut(&mut self, suit: Suit) -> &mut PlayerClock {
+        match suit {
+            Suit::Circle => &mut self.circle,
+            _ => &mut self.cross,
+        }
+    }
+}
+
+/// Parses durations such as `90`, `30s`, `5m`, `1h30m`, `2m30s` or `500ms`. Number
+/// without unit is in seconds.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    if s.is_empty() {
+        return None;
+    }
+
+    let mut res: u64 = 0;
+    let mut num: Option<u64> = None;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if let Some(d) = c.to_digit(10) {
+            num = Some(
+                num.unwrap_or_default()
+                    .checked_mul(10)?
+                    .checked_add(d as u64)?,
+            );
+            continue;
+        }
+
+        let mul = match c {
+            'h' => 3_600_000,
+            'm' if chars.next_if_eq(&'s').is_some() => 1,
+            'm' => 60_000,
+            's' => 1000,
+            _ => return None,
+        };
+        res = res.checked_add(num.take()?.checked_mul(mul)?)?;
+    }
+
+    let secs = num.unwrap_or_default().checked_mul(1000)?;
+    Some(Duration::from_millis(res.checked_add(secs)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        let ms = Duration::from_millis;
+        assert_eq!(parse_duration("90"), Some(ms(90_000)));
+        assert_eq!(parse_duration("30s"), Some(ms(30_000)));
+        assert_eq!(parse_duration("5m"), Some(ms(300_000)));
+        assert_eq!(parse_duration("500ms"), Some(ms(500)));
+        assert_eq!(parse_duration("1h30m"), Some(ms(5_400_000)));
+        assert_eq!(parse_duration("2m30"), Some(ms(150_000)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("99999999999999999999999s"), None);
+        assert_eq!(parse_duration("18446744073709551615h"), None);
+        assert_eq!(parse_duration("18446744073709551615"), None);
+    }
+
+    #[test]
+    fn test_parse_time_control() {
+        assert!(TimeControl::parse("5m").is_some());
+        assert!(TimeControl::parse("5m+3s").is_some());
+        assert!(TimeControl::parse("10m+5x30s").is_some());
+        assert!(TimeControl::parse("0+5x30s").is_some());
+        assert!(TimeControl::parse("0").is_none());
+        assert!(TimeControl::parse("0+3s").is_none());
+        assert!(TimeControl::parse("5m+0x30s").is_none());
+        assert!(TimeControl::parse("5m+5x0").is_none());
+        assert!(TimeControl::parse("5m+").is_none());
+    }
+
+    #[test]
+    fn test_undo_byo_yomi() {
+        let control = TimeControl::parse("0+3x30s").unwrap();
+        let mut clock = Clock::new(control, Suit::Cross);
+        clock.cross.left = Duration::from_secs(1);
+        clock.cross.in_overtime = true;
+
+        clock.switch(Suit::Circle);
+        assert!(clock.cross.left > Duration::from_secs(29));
+        clock.undo(Suit::Cross);
+        assert!(clock.cross.left <= Duration::from_secs(1));
+        assert_eq!(clock.cross.periods, 3);
+    }
+
+    #[test]
+    fn test_undo_increment() {
+        let control = TimeControl::parse("1m+10s").unwrap();
+        let mut clock = Clock::new(control, Suit::Cross);
+        clock.switch(Suit::Circle);
+        assert!(clock.cross.left > Duration::from_secs(69));
+        clock.undo(Suit::Cross);
+        assert!(clock.cross.left <= Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+
+use pareg::FromArg;
+
+use crate::{
+    ai::AiKind,
+    new_game::{parse_rules, parse_size, parse_suit, parse_win_len},
+    suit::Suit,
+    vec2::Vec2,
+};
+
+/// Command typed in game after `:`.
+pub enum Command {
+    /// Save the game to the file.
+    Save(PathBuf),
+    /// Load the game from the file.
+    Load(PathBuf),
+    /// Start new game on board of the given size.
+    Size(Vec2),
+    /// Start new game with the given win length.
+    Win(usize),
+    /// Undo the given number of moves.
+    Undo(usize),
+    /// Show the rules. Only freestyle is supported.
+    Rules,
+    /// Set the computer player of the suit. `None` means human player.
+    Ai(Suit, Option<AiKind>),
+    /// Start new game with the settings such as `19x19 6 o`.
+    New(String),
+}
+
+impl Command {
+    /// Names of all the commands.
+    pub const NAMES: [&'static str; 8] =
+        ["ai", "load", "new", "rules", "save", "size", "undo", "win"];
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let (name, arg) = s.split_once(' ').unwrap_or((s, ""));
+        let arg = arg.trim();
+        let missing =
+            |what: &str| Err(format!("Missing {what} for `{name}`."));
+        match name {
+            "save" | "load" if arg.is_empty() => missing("file"),
+            "save" => Ok(Self::Save(arg.into())),
+            "load" => Ok(Self::Load(arg.into())),
+            "size" if arg.is_empty() => missing("size"),
+            "size" => parse_size(arg).map(Self::Size),
+            "win" if arg.is_empty() => missing("length"),
+            "win" => parse_win_len(arg).map(Self::Win),
+            "undo" if arg.is_empty() => Ok(Self::Undo(1)),
+            "undo" => arg
+                .parse()
+                .map(Self::Undo)
+                .map_err(|_| format!("Invalid number of moves `{arg}`.")),
+            "rules" if arg.is_empty() => Ok(Self::Rules),
+            "rules" => parse_rules(arg).map(|_| Self::Rules),
+            "new" => Ok(Self::New(arg.to_owned())),
+            "ai" => {
+                let (suit, kind) = arg.split_once(' ').unwrap_or((arg, ""));
+                let suit = parse_suit(suit)?;
+                match kind.trim() {
+                    "off" | "human" => Ok(Self::Ai(suit, None)),
+                    "" => Ok(Self::Ai(suit, Some(AiKind::default()))),
+                    k => AiKind::from_arg(k)
+                        .map(|k| Self::Ai(suit, Some(k)))
+                        .map_err(|e| e.to_string()),
+                }
+            }
+            "" => Err("Missing command.".to_owned()),
+            _ => Err(format!(
+                "Unknown command `{name}`. Expected one of: {}.",
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert!(matches!(Command::parse("save a.txt"), Ok(Command::Save(_))));
+        assert!(matches!(
+            Command::parse(" load  a.txt "),
+            Ok(Command::Load(_))
+        ));
+        assert!(matches!(
+            Command::parse("size 19x15"),
+            Ok(Command::Size(s)) if s == Vec2::new(19, 15)
+        ));
+        assert!(matches!(Command::parse("win 6"), Ok(Command::Win(6))));
+        assert!(matches!(Command::parse("undo"), Ok(Command::Undo(1))));
+        assert!(matches!(Command::parse("undo 3"), Ok(Command::Undo(3))));
+        assert!(matches!(
+            Command::parse("ai o hard"),
+            Ok(Command::Ai(Suit::Circle, Some(_)))
+        ));
+        assert!(matches!(
+            Command::parse("ai x off"),
+            Ok(Command::Ai(Suit::Cross, None))
+        ));
+        assert!(matches!(Command::parse("new 9x9 o"), Ok(Command::New(_))));
+        assert!(matches!(Command::parse("rules"), Ok(Command::Rules)));
+        assert!(matches!(
+            Command::parse("rules freestyle"),
+            Ok(Command::Rules)
+        ));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for cmd in [
+            "",
+            "foo",
+            "save",
+            "size",
+            "size 0x5",
+            "size axb",
+            "win 0",
+            "undo -1",
+            "undo x",
+            "ai",
+            "ai z hard",
+            "ai x unknown",
+            "rules renju",
+        ] {
+            assert!(Command::parse(cmd).is_err(), "{cmd}");
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    dirs,
+    err::{Error, Result},
+};
+
+const CONFIG_FILE: &str = "config.ini";
+
+/// Configuration with the default values of flags. Each line of the file is
+/// `<flag> = <value>` where `<flag>` is the long name of flag without the
+/// leading `--`. Flags without value are set with `true` or `false`. Empty
+/// lines and lines starting with `#` or `;` are ignored. Lines after the
+/// section header `[keys]` are key bindings `<action> = <key>, <key>...`.
+#[derive(Default)]
+pub struct Config {
+    /// Line number and the arguments that the line stands for.
+    args: Vec<(usize, Vec<String>)>,
+    /// Line number, action and its keys.
+    keys: Vec<(usize, String, String)>,
+}
+
+impl Config {
+    /// Loads the config from the given file. Without file, the config in
+    /// the config directory is used if it exists.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let s = match path {
+            Some(path) => fs::read_to_string(path)?,
+            None => match Self::file().map(fs::read_to_string) {
+                Some(Ok(s)) => s,
+                None => return Ok(Self::default()),
+                Some(Err(e)) if e.kind() == ErrorKind::NotFound => {
+                    return Ok(Self::default())
+                }
+                Some(Err(e)) => return Err(e.into()),
+            },
+        };
+        Self::parse(&s)
+    }
+
+    /// Parses the config from its text.
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut args = vec![];
+        let mut keys = vec![];
+        let mut in_keys = false;
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+
+            let invalid = |msg: &str| Error::InvalidConfig(i + 1, msg.into());
+            if let Some(section) =
+                line.strip_prefix('[').and_then(|l| l.strip_suffix(']'))
+            {
+                if section.trim() != "keys" {
+                    return Err(invalid("Unknown section."));
+                }
+                in_keys = true;
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid("Expected `<flag> = <value>`."));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if in_keys {
+                keys.push((i + 1, key.to_owned(), value.to_owned()));
+                continue;
+            }
+            if key.is_empty() || key.starts_with('-') {
+                return Err(invalid("Expected long flag name without `--`."));
+            }
+            if matches!(key, "config" | "help") {
+                return Err(invalid("The flag can't be in config."));
+            }
+
+            match value {
+                "true" => args.push((i + 1, vec![format!("--{key}")])),
+                "false" => args.push((i + 1, vec![format!("--no-{key}")])),
+                _ => args
+                    .push((i + 1, vec![format!("--{key}"), value.to_owned()])),
+            }
+        }
+
+        Ok(Self { args, keys })
+    }
+
+    /// Gets the arguments of each line together with the line number.
+    pub fn args(&self) -> impl Iterator<Item = (usize, &[String])> {
+        self.args.iter().map(|(i, a)| (*i, a.as_slice()))
+    }
+
+    /// Gets the key bindings together with the line number.
+    pub fn keys(&self) -> impl Iterator<Item = (usize, &str, &str)> {
+        self.keys
+            .iter()
+            .map(|(i, a, k)| (*i, a.as_str(), k.as_str()))
+    }
+
+    /// Gets the path to the default config file.
+    pub fn file() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join(CONFIG_FILE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            "# comment\n\
+            ; comment\n\
+            size = 19x19\n\
+            \n\
+            threats = true\n\
+            unicode = false\n\
+            [keys]\n\
+            left = Left, h\n",
+        )
+        .unwrap();
+        let args: Vec<_> = config.args().collect();
+        assert_eq!(args.len(), 3);
+        assert_eq!(
+            args[0],
+            (3, &["--size".to_owned(), "19x19".to_owned()][..])
+        );
+        assert_eq!(args[1], (5, &["--threats".to_owned()][..]));
+        assert_eq!(args[2], (6, &["--no-unicode".to_owned()][..]));
+        let keys: Vec<_> = config.keys().collect();
+        assert_eq!(keys, [(8, "left", "Left, h")]);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for (s, line) in [
+            ("size 19x19", 1),
+            ("\n--size = 19x19", 2),
+            ("config = a.ini", 1),
+            ("help = true", 1),
+            ("= 5", 1),
+            ("[colors]", 1),
+        ] {
+            match Config::parse(s) {
+                Err(Error::InvalidConfig(l, _)) => assert_eq!(l, line, "{s}"),
+                _ => panic!("{s}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::vec2::Vec2;
+
+// Letters used for the columns. `I` is skipped as is usual in gomoku and go.
+const LETTERS: &[u8] = b"ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+/// Formats the position in the gomoku notation (e.g. `K10`). Columns are
+/// letters from the left and rows are numbers from the bottom.
+pub fn format(pos: Vec2, size: Vec2) -> String {
+    format!("{}{}", column_name(pos.x), size.y - pos.y)
+}
+
+/// Gets the letters for the column with the given index.
+pub fn column_name(mut x: usize) -> String {
+    let mut res = vec![];
+    loop {
+        res.push(LETTERS[x % LETTERS.len()]);
+        if x < LETTERS.len() {
+            break;
+        }
+        x = x / LETTERS.len() - 1;
+    }
+    res.reverse();
+    String::from_utf8(res).unwrap()
+}
+
+/// Parses position in the gomoku notation (e.g. `K10`). Returns [`None`] if
+/// it is not valid coordinate or it is outside of the board.
+pub fn parse(s: &str, size: Vec2) -> Option<Vec2> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (col, row) = s.split_at(split);
+    if col.is_empty() {
+        return None;
+    }
+
+    let mut x: usize = 0;
+    for c in col.bytes() {
+        let idx = LETTERS.iter().position(|l| *l == c.to_ascii_uppercase())?;
+        x = x.checked_mul(LETTERS.len())?.checked_add(idx + 1)?;
+    }
+    let x = x - 1;
+
+    let row: usize = row.parse().ok()?;
+    if row == 0 || row > size.y || x >= size.x {
+        return None;
+    }
+
+    Some((x, size.y - row).into())
+}
+
+/// Parses position given by numbers of the column and row (e.g. `10,12`).
+/// Both are counted from one, rows are from the bottom. Returns [`None`] if
+/// it is not valid coordinate or it is outside of the board.
+pub fn parse_numbers(s: &str, size: Vec2) -> Option<Vec2> {
+    let (x, y) = s.split_once(',')?;
+    let x: usize = x.trim().parse().ok()?;
+    let y: usize = y.trim().parse().ok()?;
+    if x == 0 || y == 0 || x > size.x || y > size.y {
+        return None;
+    }
+    Some((x - 1, size.y - y).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2 = Vec2::new(15, 15);
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format((0, 14).into(), SIZE), "A1");
+        assert_eq!(format((8, 0).into(), SIZE), "J15");
+        assert_eq!(format((7, 7).into(), SIZE), "H8");
+    }
+
+    #[test]
+    fn test_column_name() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(24), "Z");
+        assert_eq!(column_name(25), "AA");
+        assert_eq!(column_name(25 * 26 - 1), "ZZ");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("A1", SIZE), Some((0, 14).into()));
+        assert_eq!(parse(" h8 ", SIZE), Some((7, 7).into()));
+        assert_eq!(parse("J15", SIZE), Some((8, 0).into()));
+        assert_eq!(parse("AA1", (30, 1).into()), Some((25, 0).into()));
+        assert_eq!(parse("I5", SIZE), None);
+        assert_eq!(parse("A0", SIZE), None);
+        assert_eq!(parse("A16", SIZE), None);
+        assert_eq!(parse("Q1", SIZE), None);
+        assert_eq!(parse("5", SIZE), None);
+        assert_eq!(parse("A", SIZE), None);
+        assert_eq!(parse("AAAAAAAAAAAAAAAAAAAA1", SIZE), None);
+        assert_eq!(parse("A99999999999999999999999", SIZE), None);
+    }
+
+    #[test]
+    fn test_parse_format() {
+        let size = Vec2::new(40, 30);
+        for pos in Vec2::new(0, 0).to(size) {
+            assert_eq!(parse(&format(pos, size), size), Some(pos));
+        }
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        assert_eq!(parse_numbers("1,1", SIZE), Some((0, 14).into()));
+        assert_eq!(parse_numbers(" 15 , 15 ", SIZE), Some((14, 0).into()));
+        assert_eq!(parse_numbers("0,1", SIZE), None);
+        assert_eq!(parse_numbers("16,1", SIZE), None);
+        assert_eq!(parse_numbers("1", SIZE), None);
+        assert_eq!(parse_numbers("1,x", SIZE), None);
+    }
+}
@@ -0,0 +1,22 @@
+use std::{env, path::PathBuf};
+
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("ttoe"));
+    }
+    if let Some(home) = env::var_os("HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(home).join(".local/share/ttoe"));
+    }
+    env::var_os("APPDATA").map(|d| PathBuf::from(d).join("ttoe"))
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty())
+    {
+        return Some(PathBuf::from(dir).join("ttoe"));
+    }
+    if let Some(home) = env::var_os("HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(home).join(".config/ttoe"));
+    }
+    env::var_os("APPDATA").map(|d| PathBuf::from(d).join("ttoe"))
+}
@@ -0,0 +1,399 @@
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{board::Board, suit::Suit, tss::Tss, vec2::Vec2};
+
+pub const WIN_SCORE: i64 = 1 << 50;
+/// Score of win by continuous threats (VCT). It is not proven, so it is only
+/// strong advantage.
+pub const LIKELY_WIN_SCORE: i64 = WIN_SCORE / 2;
+const MAX_BREADTH: usize = 12;
+const MAX_CACHED: usize = 1 << 20;
+const DIRS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (-1, 1)];
+
+/// Heuristic alpha-beta search that works on any board size and win length.
+pub struct Engine<'a> {
+    deadline: Instant,
+    stop: Option<&'a AtomicBool>,
+    /// Evaluations of the positions by their canonical hash. The evaluation
+    /// doesn't change with rotation or reflection of the board.
+    evals: HashMap<u64, i64>,
+}
+
+/// Result of search to the given depth.
+#[derive(Debug, Clone, Copy)]
+pub struct Analysis {
+    pub best: Vec2,
+    /// Score from the point of view of the player on turn.
+    pub score: i64,
+    pub depth: usize,
+    /// The player on turn likely wins by VCT in the given number of moves.
+    pub likely_win: Option<usize>,
+}
+
+impl Analysis {
+    /// Gets the number of moves of the winning player until the win if the
+    /// search found forced win. Positive if the player on turn wins and
+    /// negative if the player on turn loses.
+    pub fn win_in(&self) -> Option<isize> {
+        if self.score.abs() < WIN_SCORE {
+            return None;
+        }
+        let plies =
+            self.depth as isize + 1 - (self.score.abs() - WIN_SCORE) as isize;
+        let moves = (plies + 1) / 2;
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+}
+
+impl<'a> Engine<'a> {
+    /// Finds the best move for the player on turn. The search stops once
+    /// `budget` runs out.
+    pub fn best_move(board: &Board, budget: Duration) -> Option<Vec2> {
+        let mut best = None;
+        Self::analyze(board, budget, None, |a| best = Some(a.best));
+        best
+    }
+
+    /// Searches with iterative deepening until `budget` runs out, `stop` is
+    /// set or the result is known. `on_depth` is called with the result of
+    /// each completed depth.
+    pub fn analyze(
+        board: &Board,
+        budget: Duration,
+        stop: Option<&'a AtomicBool>,
+        mut on_depth: impl FnMut(Analysis),
+    ) {
+        // The threat search counts to the budget.
+        let deadline = Instant::now() + budget;
+        if board.on_turn().is_none() {
+            return;
+        }
+
+        let mut board = board.clone();
+        let mut moves = candidates(&board);
+        let Some(&first) = moves.first() else {
+            return;
+        };
+        if moves.len() == 1 || move_score(&board, first) >= WIN_SCORE {
+            on_depth(Analysis {
+                best: first,
+                score: if moves.len() == 1 { 0 } else { WIN_SCORE + 1 },
+                depth: 1,
+                likely_win: None,
+            });
+            return;
+        }
+        moves.truncate(MAX_BREADTH);
+
+        if let Some(line) = Tss::vcf_with_stop(&board, budget / 8, stop) {
+            on_depth(Analysis {
+                best: line[0],
+                score: WIN_SCORE + 1,
+                depth: line.len(),
+                likely_win: None,
+            });
+            return;
+        }
+        if let Some(line) = Tss::vct_with_stop(&board, budget / 8, stop) {
+            on_depth(Analysis {
+                best: line[0],
+                score: LIKELY_WIN_SCORE,
+                depth: line.len(),
+                likely_win: Some(line.len().div_ceil(2)),
+            });
+            return;
+        }
+
+        let mut engine = Self {
+            deadline,
+            stop,
+            evals: HashMap::new(),
+        };
+
+        for depth in 1..=board.empty_count() {
+            let Some((best, score)) = engine.root(&mut board, &moves, depth)
+            else {
+                break;
+            };
+            on_depth(Analysis {
+                best,
+                score,
+                depth,
+                likely_win: None,
+            });
+            if score.abs() >= WIN_SCORE {
+                break;
+            }
+        }
+    }
+
+    fn root(
+        &mut self,
+        board: &mut Board,
+        moves: &[Vec2],
+        depth: usize,
+    ) -> Option<(Vec2, i64)> {
+        let mut alpha = -i64::MAX;
+        let mut best = moves[0];
+        for &mov in moves {
+            let score = self.score_move(board, mov, depth, alpha, i64::MAX)?;
+            if score > alpha {
+                alpha = score;
+                best = mov;
+            }
+        }
+        Some((best, alpha))
+    }
+
+    fn negamax(
+        &mut self,
+        board: &mut Board,
+        depth: usize,
+        mut alpha: i64,
+        beta: i64,
+    ) -> Option<i64> {
+        if Instant::now() > self.deadline
+            || self.stop.is_some_and(|s| s.load(Ordering::Relaxed))
+        {
+            return None;
+        }
+
+        let mut moves = candidates(board);
+        if moves.is_empty() {
+            return Some(0);
+        }
+        moves.truncate(MAX_BREADTH);
+
+        for mov in moves {
+            let score = self.score_move(board, mov, depth, alpha, beta)?;
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        Some(alpha)
+    }
+
+    fn score_move(
+        &mut self,
+        board: &mut Board,
+        mov: Vec2,
+        depth: usize,
+        alpha: i64,
+        beta: i64,
+    ) -> Option<i64> {
+        _ = board.play_at(mov);
+        let score = if board.wins_at(mov) {
+            // Prefer faster wins
+            Some(WIN_SCORE + depth as i64)
+        } else if depth <= 1 {
+            Some(-self.evaluate(board))
+        } else {
+            self.negamax(board, depth - 1, -beta, -alpha).map(|s| -s)
+        };
+        board.undo();
+        score
+    }
+
+    fn evaluate(&mut self, board: &Board) -> i64 {
+        if self.evals.len() >= MAX_CACHED {
+            self.evals.clear();
+        }
+        *self
+            .evals
+            .entry(board.canonical_hash())
+            .or_insert_with(|| evaluate(board))
+    }
+}
+
+/// Evaluates the position from the point of view of the player on turn.
+fn evaluate(board: &Board) -> i64 {
+    let me = board.on_turn();
+    if me.is_none() {
+        return 0;
+    }
+
+    let win_len = board.win_len();
+    let mut mine = 0;
+    let mut theirs = 0;
+    let mut threats = 0;
+    for_each_window(board, |own, other| {
+        if other == 0 {
+            if own + 1 == win_len {
+                threats += 1;
+            }
+            mine += weight(own, win_len);
+        } else if own == 0 {
+            theirs += weight(other, win_len);
+        }
+    });
+
+    if threats != 0 {
+        // The player on turn can win with the next move.
+        return WIN_SCORE / 2;
+    }
+
+    mine - theirs
+}
+
+/// Gets the empty cells near the stones ordered from the most promising.
+fn candidates(board: &Board) -> Vec<Vec2> {
+    let size = board.size();
+    let mut res: Vec<_> = Vec2::new(0, 0)
+        .to(size)
+        .filter(|p| board[*p].is_none() && has_neighbour(board, *p))
+        .collect();
+
+    if res.is_empty() {
+        // Cells in the center are part of more windows so they will be
+        // preffered.
+        res = Vec2::new(0, 0)
+            .to(size)
+            .filter(|p| board[*p].is_none())
+            .collect();
+    }
+
+    let mut scored: Vec<_> =
+        res.into_iter().map(|p| (move_score(board, p), p)).collect();
+    scored.sort_by_key(|(s, _)| std::cmp::Reverse(*s));
+    scored.into_iter().map(|(_, p)| p).collect()
+}
+
+/// Scores how good is playing at the empty cell `pos` for the player on turn
+/// both for attack and defense.
+fn move_score(board: &Board, pos: Vec2) -> i64 {
+    let me = board.on_turn();
+    let win_len = board.win_len();
+    let mut attack: i64 = 0;
+    let mut defense: i64 = 0;
+    for dir in DIRS {
+        let dir = Vec2::from(dir);
+        for off in 0..win_len {
+            let Some((own, other)) = count_window(board, pos, dir, off, me)
+            else {
+                continue;
+            };
+            if other == 0 {
+                attack = attack.saturating_add(weight(own + 1, win_len));
+            } else if own == 0 {
+                defense = defense.saturating_add(if other + 1 == win_len {
+                    WIN_SCORE / 4
+                } else {
+                    weight(other + 1, win_len)
+                });
+            }
+        }
+    }
+    attack.saturating_add(defense * 3 / 4)
+}
+
+/// Calls `f` with counts of stones of the player on turn and the oposite
+/// player in each window of the win length on the board.
+fn for_each_window(board: &Board, mut f: impl FnMut(usize, usize)) {
+    let me = board.on_turn();
+    let win_len = board.win_len();
+    for (start, dir, len) in lines(board.size()) {
+        if len < win_len {
+            continue;
+        }
+
+        let mut own = 0;
+        let mut other = 0;
+        let mut pos = start;
+        let mut tail = start;
+        for i in 0..len {
+            let suit = board[pos];
+            if suit == me {
+                own += 1;
+            } else if !suit.is_none() {
+                other += 1;
+            }
+
+            if i + 1 >= win_len {
+                f(own, other);
+                let suit = board[tail];
+                if suit == me {
+                    own -= 1;
+                } else if !suit.is_none() {
+                    other -= 1;
+                }
+                tail = tail.wrapping_add_signed(dir);
+            }
+            pos = pos.wrapping_add_signed(dir);
+        }
+    }
+}
+
+/// Gets all the lines on board of the given size as start, direction and
+/// length.
+fn lines(size: Vec2) -> impl Iterator<Item = (Vec2, Vec2<isize>, usize)> {
+    let (w, h) = size.tuple();
+    let rows = (0..h).map(move |y| ((0, y).into(), (1, 0).into(), w));
+    let cols = (0..w).map(move |x| ((x, 0).into(), (0, 1).into(), h));
+    let diag = (0..w)
+        .map(move |x| ((x, 0).into(), (1, 1).into(), (w - x).min(h)))
+        .chain(
+            (1..h).map(move |y| ((0, y).into(), (1, 1).into(), w.min(h - y))),
+        );
+    let anti =
+        (0..w)
+            .map(move |x| ((x, 0).into(), (-1, 1).into(), (x + 1).min(h)))
+            .chain((1..h).map(move |y| {
+                ((w - 1, y).into(), (-1, 1).into(), w.min(h - y))
+            }));
+    rows.chain(cols).chain(diag).chain(anti)
+}
+
+/// Counts stones of `me` and the other player in the window that contains
+/// `pos` at offset `off` from its start. Returns [`None`] if the window
+/// doesn't fit the board.
+fn count_window(
+    board: &Board,
+    pos: Vec2,
+    dir: Vec2<isize>,
+    off: usize,
+    me: Suit,
+) -> Option<(usize, usize)> {
+    let win_len = board.win_len() as isize;
+    let off = off as isize;
+    let start = pos.signed() - dir.cmul((off, off));
+    let end = start + dir.cmul((win_len - 1, win_len - 1));
+    if !board.contains_signed(start) || !board.contains_signed(end) {
+        return None;
+    }
+
+    let mut own = 0;
+    let mut other = 0;
+    let mut p = start.unsigned();
+    for _ in 0..win_len {
+        let suit = board[p];
+        if suit == me {
+            own += 1;
+        } else if !suit.is_none() {
+            other += 1;
+        }
+        p = p.wrapping_add_signed(dir);
+    }
+    Some((own, other))
+}
+
+fn has_neighbour(board: &Board, pos: Vec2) -> bool {
+    let start = pos.saturating_sub((2, 2));
+    let end = (pos + (3, 3)).cmin(board.size());
+    start.to(end).any(|p| !board[p].is_none())
+}
+
+fn weight(cnt: usize, win_len: usize) -> i64 {
+    if cnt == 0 {
+        0
+    } else if cnt >= win_len {
+        WIN_SCORE
+    } else {
+        8i64.saturating_pow(cnt as u32 - 1)
+    }
+}
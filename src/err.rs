@@ -6,9 +6,35 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     #[error("Can't place there. The place is already populated.")]
     AlreadyPopulated,
+    #[error("The starting position is already won.")]
+    PositionWon,
+    #[error("The starting position has no empty cells.")]
+    PositionFull,
+    #[error("The win length {0} doesn't fit on the {1}x{2} board.")]
+    WinLength(usize, usize, usize),
+    #[error("Invalid line {0} in the stats file.")]
+    InvalidStats(usize),
+    #[error("Invalid line {0} in the opening book.")]
+    InvalidBook(usize),
+    #[error("Invalid line {0} in the puzzles file.")]
+    InvalidPuzzle(usize),
+    #[error(
+        "There are no puzzles. Create them with `ttoe puzzle build <games-dir>`."
+    )]
+    NoPuzzles,
+    #[error("Invalid line {0} in the config file: {1}")]
+    InvalidConfig(usize, String),
+    #[error("Failed to find the data directory.")]
+    NoDataDir,
+    #[error("Invalid game file: {0}")]
+    InvalidGame(String),
+    #[error("Engine `{0}` failed: {1}")]
+    Engine(String, String),
     #[error("Rage quit :)")]
     RageQuit,
     #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
     Termal(#[from] termal::error::Error),
     #[error(transparent)]
     Pareg(#[from] pareg::ArgError),
@@ -0,0 +1,187 @@
+use std::{fmt::Write, fs, path::Path};
+
+use crate::{
+    board::Board,
+    coord,
+    err::{Error, Result},
+    position::Position,
+    suit::Suit,
+    vec2::Vec2,
+};
+
+/// Saved game. The file has one item per line:
+///
+/// ```txt
+/// size 15x15
+/// win 5
+/// start <position>
+/// moves H8 J9 H9
+/// ```
+///
+/// `size` is ignored if `start` is given. `start` has the same format as the
+/// argument `--position` with rows separated by `/`. Moves are in the
+/// gomoku notation and may be on multiple `moves` lines. Lines starting with
+/// `#` are comments.
+pub struct Game {
+    start: Position,
+    win_len: usize,
+    moves: Vec<Vec2>,
+}
+
+impl Game {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let s = fs::read_to_string(path)?;
+
+        let mut size = None;
+        let mut start = None;
+        let mut win_len = None;
+        let mut moves = vec![];
+        for (i, line) in s.lines().enumerate() {
+            let err = |msg: &str| {
+                Error::InvalidGame(format!("line {}: {msg}", i + 1))
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, val) = line.split_once(' ').unwrap_or((line, ""));
+            let val = val.trim();
+            match key {
+                "size" => {
+                    let (w, h) = val
+                        .split_once('x')
+                        .and_then(|(w, h)| {
+                            Some((w.parse().ok()?, h.parse().ok()?))
+                        })
+                        .filter(|(w, h)| *w != 0 && *h != 0)
+                        .ok_or_else(|| err("Invalid size."))?;
+                    size = Some(Vec2::new(w, h));
+                }
+                "win" => {
+                    win_len = Some(
+                        val.parse()
+                            .ok()
+                            .filter(|w| *w != 0)
+                            .ok_or_else(|| err("Invalid win length."))?,
+                    );
+                }
+                "start" => start = Some(Position::parse(val).map_err(err)?),
+                "moves" => moves.extend(val.split_whitespace()),
+                _ => return Err(err("Unknown item.")),
+            }
+        }
+
+        let start = start
+            .or_else(|| size.map(Position::empty))
+            .ok_or_else(|| Error::InvalidGame("Missing size.".into()))?;
+        let size = start.size();
+        let moves = moves
+            .into_iter()
+            .map(|m| {
+                coord::parse(m, size).ok_or_else(|| {
+                    Error::InvalidGame(format!("Invalid move `{m}`."))
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            win_len: win_len.unwrap_or_else(|| size.max().min(5)),
+            start,
+            moves,
+        })
+    }
+
+    /// Creates game from the moves played on the board.
+    pub fn from_board(board: &Board) -> Self {
+        Self {
+            start: board.start().clone(),
+            win_len: board.win_len(),
+            moves: board.moves().to_vec(),
+        }
+    }
+
+    /// Saves the game to the file. `comments` are written at the top of the
+    /// file.
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        comments: &[String],
+    ) -> Result<()> {
+        let size = self.start.size();
+        let mut s = String::new();
+        for c in comments {
+            _ = writeln!(s, "# {c}");
+        }
+        if self.start.is_empty() && self.start.on_turn() == Suit::Cross {
+            _ = writeln!(s, "size {}x{}", size.x, size.y);
+        } else {
+            _ = writeln!(s, "start {}", self.start);
+        }
+        _ = writeln!(s, "win {}", self.win_len);
+        s += "moves";
+        for m in &self.moves {
+            s.push(' ');
+            s += &coord::format(*m, size);
+        }
+        s.push('\n');
+        fs::write(path, s)?;
+        Ok(())
+    }
+
+    pub fn moves(&self) -> &[Vec2] {
+        &self.moves
+    }
+
+    /// Creates board with the starting position of the game.
+    pub fn board(&self) -> Result<Board> {
+        Board::new(self.start.clone(), self.win_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn round_trip(board: &Board, name: &str) -> Board {
+        let path = env::temp_dir()
+            .join(format!("ttoe-test-{}-{name}.txt", std::process::id()));
+        Game::from_board(board)
+            .save(&path, &["test".into()])
+            .unwrap();
+        let game = Game::load(&path);
+        _ = fs::remove_file(&path);
+        let game = game.unwrap();
+        let mut res = game.board().unwrap();
+        for m in game.moves() {
+            res.play_at(*m).unwrap();
+        }
+        res
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut board = Board::new(Position::empty((9, 7)), 4).unwrap();
+        for pos in [(4, 3), (0, 0), (8, 6)] {
+            board.play_at(pos.into()).unwrap();
+        }
+        let loaded = round_trip(&board, "save");
+        assert!(loaded == board);
+        assert_eq!(loaded.moves(), board.moves());
+        assert_eq!(loaded.win_len(), 4);
+    }
+
+    #[test]
+    fn test_save_load_circle_starts() {
+        let mut board = Board::new(Position::empty((9, 9)), 5).unwrap();
+        board.reset_with(Suit::Circle);
+        for pos in [(4, 4), (3, 3), (2, 2)] {
+            board.play_at(pos.into()).unwrap();
+        }
+        let loaded = round_trip(&board, "circle");
+        assert!(loaded == board);
+        assert_eq!(loaded[(4, 4)], Suit::Circle);
+    }
+}
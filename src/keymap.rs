@@ -0,0 +1,501 @@
+use std::fmt::Display;
+
+use termal::raw::events::{Key, KeyCode, Modifiers};
+
+/// Command that may be bound to keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Left,
+    Down,
+    Right,
+    Play,
+    Undo,
+    Reset,
+    Quit,
+    JumpUp,
+    JumpLeft,
+    JumpDown,
+    JumpRight,
+    Center,
+    ScrollUp,
+    ScrollLeft,
+    ScrollDown,
+    ScrollRight,
+    ToggleColor,
+    ClearMsg,
+    RageQuit,
+    ToggleThreats,
+    ToggleEval,
+    Hint,
+    Next,
+    ZoomIn,
+    ZoomOut,
+    ToggleLabels,
+    ToggleNumbers,
+    Goto,
+    Command,
+    NewGame,
+    Help,
+}
+
+/// Key with modifiers as it may be bound to action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBind {
+    code: KeyCode,
+    modifiers: Modifiers,
+}
+
+/// Keys bound to the actions. Key may be bound to only one action, but
+/// action may have any number of keys.
+#[derive(Clone)]
+pub struct Keymap {
+    binds: Vec<(Action, Vec<KeyBind>)>,
+}
+
+impl Action {
+    pub const ALL: [Action; 32] = [
+        Self::Up,
+        Self::Left,
+        Self::Down,
+        Self::Right,
+        Self::Play,
+        Self::Undo,
+        Self::Reset,
+        Self::Quit,
+        Self::JumpUp,
+        Self::JumpLeft,
+        Self::JumpDown,
+        Self::JumpRight,
+        Self::Center,
+        Self::ScrollUp,
+        Self::ScrollLeft,
+        Self::ScrollDown,
+        Self::ScrollRight,
+        Self::ToggleColor,
+        Self::ClearMsg,
+        Self::RageQuit,
+        Self::ToggleThreats,
+        Self::ToggleEval,
+        Self::Hint,
+        Self::Next,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::ToggleLabels,
+        Self::ToggleNumbers,
+        Self::Goto,
+        Self::Command,
+        Self::NewGame,
+        Self::Help,
+    ];
+
+    /// Name of the action in the config file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Left => "left",
+            Self::Down => "down",
+            Self::Right => "right",
+            Self::Play => "play",
+            Self::Undo => "undo",
+            Self::Reset => "reset",
+            Self::Quit => "quit",
+            Self::JumpUp => "jump-up",
+            Self::JumpLeft => "jump-left",
+            Self::JumpDown => "jump-down",
+            Self::JumpRight => "jump-right",
+            Self::Center => "center",
+            Self::ScrollUp => "scroll-up",
+            Self::ScrollLeft => "scroll-left",
+            Self::ScrollDown => "scroll-down",
+            Self::ScrollRight => "scroll-right",
+            Self::ToggleColor => "toggle-color",
+            Self::ClearMsg => "clear-msg",
+            Self::RageQuit => "rage-quit",
+            Self::ToggleThreats => "toggle-threats",
+            Self::ToggleEval => "toggle-eval",
+            Self::Hint => "hint",
+            Self::Next => "next",
+            Self::ZoomIn => "zoom-in",
+            Self::ZoomOut => "zoom-out",
+            Self::ToggleLabels => "toggle-labels",
+            Self::ToggleNumbers => "toggle-numbers",
+            Self::Goto => "goto",
+            Self::Command => "command",
+            Self::NewGame => "new-game",
+            Self::Help => "help",
+        }
+    }
+
+    /// Description of the action for the help.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Up => "Move the selected cell (cursor) up.",
+            Self::Left => "Move the cursor left.",
+            Self::Down => "Move the cursor down.",
+            Self::Right => "Move the cursor right.",
+            Self::Play => "Play at the selected cell (cursor).",
+            Self::Undo => {
+                "Undo last turn. With chess clock, the time spent is not \
+                returned."
+            }
+            Self::Reset => {
+                "Reset. Start a new game. In match, this starts the next game \
+                after the\n    current game has finished or restarts the \
+                current game."
+            }
+            Self::Quit => "Quit",
+            Self::JumpUp => {
+                "Move the cursor up to the first empty space after occupated \
+                space."
+            }
+            Self::JumpLeft => "The same as above but to the left.",
+            Self::JumpDown => "The same as above but down.",
+            Self::JumpRight => "The same as above but to the right.",
+            Self::Center => "Move cursor to the center of the board.",
+            Self::ScrollUp => {
+                "Scroll up (when the board doesn't fit the terminal)."
+            }
+            Self::ScrollLeft => "Scroll left.",
+            Self::ScrollDown => "Scroll down.",
+            Self::ScrollRight => "Scroll right.",
+            Self::ToggleColor => "Toggle color.",
+            Self::ClearMsg => "Clear the last message.",
+            Self::RageQuit => "Rage quit.",
+            Self::ToggleThreats => "Toggle showing of threats.",
+            Self::ToggleEval => {
+                "Toggle the evaluation bar. The evaluation shows who is ahead \
+                or who has\n    forced win. It is computed in the background \
+                after each move."
+            }
+            Self::Hint => {
+                "Move the cursor to the move suggested by the engine."
+            }
+            Self::Next => {
+                "Go to the next puzzle or to the next step of the tutorial."
+            }
+            Self::ZoomIn => "Make the cells larger (2x1, 3x1 and 4x2 characters).",
+            Self::ZoomOut => {
+                "Make the cells smaller to fit larger boards without scrolling."
+            }
+            Self::ToggleLabels => {
+                "Toggle the coordinates of rows and columns around the board."
+            }
+            Self::ToggleNumbers => {
+                "Toggle showing of move numbers instead of the symbols."
+            }
+            Self::Goto => {
+                "Move the cursor to the typed coordinate such as K10 or 10,12 \
+                (column and\n    row number)."
+            }
+            Self::Command => "Type command (see below).",
+            Self::NewGame => {
+                "Start new game with different size, win length or starting \
+                player. Press\n    [Enter] to keep the value in brackets and \
+                [Esc] to cancel."
+            }
+            Self::Help => "Show short help.",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+impl KeyBind {
+    fn new(code: KeyCode, modifiers: Modifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    fn code(code: KeyCode) -> Self {
+        Self::new(code, Modifiers::NONE)
+    }
+
+    fn chr(c: char) -> Self {
+        if c.is_uppercase() {
+            Self::new(KeyCode::Char(c.to_ascii_lowercase()), Modifiers::SHIFT)
+        } else {
+            Self::code(KeyCode::from_char(c))
+        }
+    }
+
+    /// Parses key such as `w`, `W`, `Ctrl+w`, `Shift+Up` or `Enter`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut modifiers = Modifiers::NONE;
+        let mut rest = s;
+        while let Some((m, r)) =
+            rest.split_once('+').filter(|(_, r)| !r.is_empty())
+        {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => Modifiers::CONTROL,
+                "alt" => Modifiers::ALT,
+                "shift" => Modifiers::SHIFT,
+                _ => return None,
+            };
+            rest = r;
+        }
+
+        let mut chars = rest.chars();
+        let key = match (chars.next()?, chars.next()) {
+            (c, None) => Self::chr(c),
+            _ => Self::code(match rest.to_ascii_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "space" => KeyCode::Space,
+                "tab" => KeyCode::Tab,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pgup" => KeyCode::PgUp,
+                "pgdown" => KeyCode::PgDown,
+                _ => return None,
+            }),
+        };
+
+        Some(Self::new(key.code, key.modifiers | modifiers))
+    }
+
+    fn is_arrow(&self) -> bool {
+        matches!(
+            self.code,
+            KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right
+        )
+    }
+}
+
+impl Display for KeyBind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut m = self.modifiers;
+        if m.contains(Modifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if m.contains(Modifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if let KeyCode::Char(c) = self.code {
+            if m.contains(Modifiers::SHIFT) && c.is_lowercase() {
+                m.remove(Modifiers::SHIFT);
+                return write!(f, "{}", c.to_ascii_uppercase());
+            }
+        }
+        if m.contains(Modifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::PgUp => write!(f, "PgUp"),
+            KeyCode::PgDown => write!(f, "PgDown"),
+            c => write!(f, "{c:?}"),
+        }
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use KeyCode::*;
+
+        let ctrl = |c| KeyBind::new(c, Modifiers::CONTROL);
+        let shift = |c| KeyBind::new(c, Modifiers::SHIFT);
+        let binds = Action::ALL
+            .into_iter()
+            .map(|a| {
+                let keys = match a {
+                    Action::Up => vec![KeyBind::code(Up), KeyBind::chr('w')],
+                    Action::Left => {
+                        vec![KeyBind::code(Left), KeyBind::chr('a')]
+                    }
+                    Action::Down => {
+                        vec![KeyBind::code(Down), KeyBind::chr('s')]
+                    }
+                    Action::Right => {
+                        vec![KeyBind::code(Right), KeyBind::chr('d')]
+                    }
+                    Action::Play => vec![
+                        KeyBind::code(Enter),
+                        KeyBind::code(Space),
+                        KeyBind::chr('0'),
+                    ],
+                    Action::Undo => vec![KeyBind::chr('u')],
+                    Action::Reset => vec![KeyBind::chr('r')],
+                    Action::Quit => vec![KeyBind::chr('q')],
+                    Action::JumpUp => vec![shift(Up), KeyBind::chr('W')],
+                    Action::JumpLeft => vec![shift(Left), KeyBind::chr('A')],
+                    Action::JumpDown => vec![shift(Down), KeyBind::chr('S')],
+                    Action::JumpRight => {
+                        vec![shift(Right), KeyBind::chr('D')]
+                    }
+                    Action::Center => vec![KeyBind::chr('c')],
+                    Action::ScrollUp => vec![ctrl(Up), ctrl(Char('w'))],
+                    Action::ScrollLeft => vec![ctrl(Left), ctrl(Char('a'))],
+                    Action::ScrollDown => vec![ctrl(Down), ctrl(Char('s'))],
+                    Action::ScrollRight => {
+                        vec![ctrl(Right), ctrl(Char('d'))]
+                    }
+                    Action::ToggleColor => {
+                        vec![KeyBind::new(Char('c'), Modifiers::ALT)]
+                    }
+                    Action::ClearMsg => vec![KeyBind::chr('C')],
+                    Action::RageQuit => vec![ctrl(Char('c'))],
+                    Action::ToggleThreats => vec![KeyBind::chr('t')],
+                    Action::ToggleEval => vec![KeyBind::chr('e')],
+                    Action::Hint => vec![KeyBind::chr('H')],
+                    Action::Next => vec![KeyBind::chr('n')],
+                    Action::ZoomIn => {
+                        vec![KeyBind::chr('+'), KeyBind::chr('=')]
+                    }
+                    Action::ZoomOut => vec![KeyBind::chr('-')],
+                    Action::ToggleLabels => vec![KeyBind::chr('l')],
+                    Action::ToggleNumbers => vec![KeyBind::chr('m')],
+                    Action::Goto => vec![KeyBind::chr('g')],
+                    Action::Command => vec![KeyBind::chr(':')],
+                    Action::NewGame => vec![KeyBind::chr('N')],
+                    Action::Help => vec![KeyBind::chr('h')],
+                };
+                (a, keys)
+            })
+            .collect();
+        Self { binds }
+    }
+}
+
+impl Keymap {
+    /// Gets the action bound to the pressed key.
+    pub fn action(&self, key: &Key) -> Option<Action> {
+        let key = KeyBind::new(key.code, key.modifiers);
+        self.binds
+            .iter()
+            .find(|(_, k)| k.contains(&key))
+            .map(|(a, _)| *a)
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyBind] {
+        self.binds
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, k)| k.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Binds the keys to the action instead of its current keys. The keys
+    /// are unbound from other actions.
+    pub fn bind(&mut self, action: Action, keys: Vec<KeyBind>) {
+        for (a, k) in &mut self.binds {
+            if *a == action {
+                *k = keys.clone();
+            } else {
+                k.retain(|k| !keys.contains(k));
+            }
+        }
+    }
+
+    /// Parses the binding from the config file. `name` is the name of the
+    /// action and `keys` is comma separated list of keys.
+    pub fn bind_str(&mut self, name: &str, keys: &str) -> Result<(), String> {
+        let action = Action::from_name(name)
+            .ok_or_else(|| format!("Unknown action `{name}`."))?;
+        let keys = keys
+            .split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(|k| {
+                KeyBind::parse(k).ok_or_else(|| format!("Invalid key `{k}`."))
+            })
+            .collect::<Result<_, _>>()?;
+        self.bind(action, keys);
+        Ok(())
+    }
+
+    /// Formats the keys of the action as for example `Up/w`.
+    pub fn format_keys(&self, action: Action) -> String {
+        let keys: Vec<_> =
+            self.keys(action).iter().map(|k| k.to_string()).collect();
+        if keys.is_empty() {
+            "unbound".to_owned()
+        } else {
+            keys.join("/")
+        }
+    }
+
+    /// Gets the short help with the most important actions.
+    pub fn short_help(&self) -> String {
+        let dirs = [Action::Up, Action::Left, Action::Down, Action::Right];
+        let arrows = dirs.iter().all(|d| {
+            self.keys(*d)
+                .iter()
+                .any(|k| k.is_arrow() && k.modifiers.is_empty())
+        });
+        let letters: String = dirs
+            .iter()
+            .filter_map(|d| self.keys(*d).iter().find(|k| !k.is_arrow()))
+            .map(|k| k.to_string())
+            .collect();
+        let mut moves: Vec<_> =
+            arrows.then(|| "Arrows".to_owned()).into_iter().collect();
+        moves.extend((!letters.is_empty()).then_some(letters));
+
+        let mut res = format!("[{}]move", moves.join("/"));
+        for (action, name) in [
+            (Action::Play, "play"),
+            (Action::Quit, "quit"),
+            (Action::Reset, "restart"),
+            (Action::Undo, "undo"),
+            (Action::Hint, "hint"),
+            (Action::ToggleEval, "eval"),
+            (Action::Help, "help"),
+        ] {
+            res += &format!(" [{}]{name}", self.format_keys(action));
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let key = |c, m| Some(KeyBind::new(c, m));
+        assert_eq!(
+            KeyBind::parse("w"),
+            key(KeyCode::Char('w'), Modifiers::NONE)
+        );
+        assert_eq!(
+            KeyBind::parse("W"),
+            key(KeyCode::Char('w'), Modifiers::SHIFT)
+        );
+        assert_eq!(
+            KeyBind::parse("Ctrl+w"),
+            key(KeyCode::Char('w'), Modifiers::CONTROL)
+        );
+        assert_eq!(
+            KeyBind::parse("shift+Up"),
+            key(KeyCode::Up, Modifiers::SHIFT)
+        );
+        assert_eq!(
+            KeyBind::parse("+"),
+            key(KeyCode::Char('+'), Modifiers::NONE)
+        );
+        assert_eq!(
+            KeyBind::parse("Alt++"),
+            key(KeyCode::Char('+'), Modifiers::ALT)
+        );
+        assert_eq!(KeyBind::parse(""), None);
+        assert_eq!(KeyBind::parse("Foo"), None);
+        assert_eq!(KeyBind::parse("Super+w"), None);
+    }
+
+    #[test]
+    fn test_parse_display() {
+        for s in ["w", "W", "Ctrl+w", "Shift+Up", "Alt+Enter", "PgUp", "+"] {
+            assert_eq!(KeyBind::parse(s).unwrap().to_string(), s);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use pareg::{ArgError, FromArg};
+
+use crate::vec2::Vec2;
+
+/// Size and look of the cells on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// Cells of 4x2 characters surrounded by grid.
+    #[default]
+    Full,
+    /// Cells of 3x1 characters on horizontal grid lines.
+    Wide,
+    /// Cells of 2x1 characters without grid.
+    Compact,
+}
+
+impl Layout {
+    /// Gets the number of characters taken by one cell.
+    pub fn cell(&self) -> Vec2 {
+        match self {
+            Self::Full => (4, 2).into(),
+            Self::Wide => (3, 1).into(),
+            Self::Compact => (2, 1).into(),
+        }
+    }
+
+    /// Gets the number of characters taken by board with the given number
+    /// of cells (without the message line).
+    pub fn draw_size(&self, cells: Vec2) -> Vec2 {
+        cells.cmul(self.cell()) + self.extra()
+    }
+
+    /// Gets the number of cells that fit in the given space (including the
+    /// message line).
+    pub fn cells(&self, space: Vec2) -> Vec2 {
+        (space.saturating_sub(self.extra() + (0, 1)))
+            .cdiv(self.cell())
+            .cmax((1, 1))
+    }
+
+    /// Gets the position of the symbol within cell.
+    pub fn symbol(&self) -> Vec2 {
+        match self {
+            Self::Full => (2, 1).into(),
+            Self::Wide | Self::Compact => (1, 0).into(),
+        }
+    }
+
+    /// Gets the number of digits of move number that fit in cell.
+    pub fn digits(&self) -> usize {
+        match self {
+            Self::Full | Self::Wide => 3,
+            Self::Compact => 2,
+        }
+    }
+
+    /// Gets the layout with larger cells.
+    pub fn zoom_in(&self) -> Self {
+        match self {
+            Self::Full | Self::Wide => Self::Full,
+            Self::Compact => Self::Wide,
+        }
+    }
+
+    /// Gets the layout with smaller cells.
+    pub fn zoom_out(&self) -> Self {
+        match self {
+            Self::Full => Self::Wide,
+            Self::Wide | Self::Compact => Self::Compact,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Full => "4x2",
+            Self::Wide => "3x1",
+            Self::Compact => "2x1",
+        }
+    }
+
+    /// Characters taken by the grid after the last cell.
+    fn extra(&self) -> Vec2 {
+        match self {
+            Self::Full => (1, 1).into(),
+            Self::Wide => (0, 0).into(),
+            Self::Compact => (1, 0).into(),
+        }
+    }
+}
+
+impl<'a> FromArg<'a> for Layout {
+    fn from_arg(arg: &'a str) -> pareg::Result<Self> {
+        match arg {
+            "4x2" => Ok(Self::Full),
+            "3x1" => Ok(Self::Wide),
+            "2x1" => Ok(Self::Compact),
+            _ => Err(ArgError::FailedToParse {
+                typ: "layout",
+                value: arg.to_owned().into(),
+                msg: Some("Expected `4x2`, `3x1` or `2x1`.".into()),
+            }),
+        }
+    }
+}
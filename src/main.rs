@@ -1,32 +1,61 @@
 use std::{borrow::Cow, env, process::ExitCode};
 
-use args::Args;
+use args::{Args, Command};
 use board::Board;
+use book::Book;
 use err::{Error, Result};
+use keymap::Action;
 use mainloop::Mainloop;
-use termal::{eprintcln, gradient, printmcln};
+use puzzle::Puzzles;
+use stats::Stats;
+use termal::{eprintcln, formatmc, gradient, printmcln};
+use tutorial::Tutorial;
 
+mod ai;
+mod analyze;
+mod analyzer;
 mod append_str;
 mod args;
 mod board;
 mod board_gui;
+mod book;
+mod clock;
+mod command;
+mod config;
+mod coord;
+mod dirs;
 mod draw_buffer;
+mod engine;
 mod err;
+mod game;
+mod keymap;
+mod layout;
 mod mainloop;
+mod match_score;
+mod mcts;
+mod new_game;
+mod piskvork;
+mod position;
+mod prompt;
+mod puzzle;
+mod rng;
+mod selfplay;
 mod slice_2d;
+mod stats;
 mod suit;
+mod theme;
+mod threats;
+mod tournament;
+mod tss;
+mod tutorial;
 mod vec2;
 mod vec2_range;
+mod zobrist;
 
 fn main() -> ExitCode {
     match start() {
         Ok(_) => ExitCode::SUCCESS,
-        Err(e) if matches!(e, Error::Pareg(_)) => {
-            eprintcln!("{'r}error: {'_}{e}");
-            ExitCode::FAILURE
-        }
         Err(e) => {
-            _ = Mainloop::restore();
             eprintcln!("{'r}error: {'_}{e}");
             ExitCode::FAILURE
         }
@@ -42,13 +71,70 @@ fn start() -> Result<()> {
         return Ok(());
     }
 
-    Mainloop::prepare()?;
+    let mut puzzles = None;
+    let mut tutorial = None;
+    match args.command() {
+        Command::Play => {}
+        Command::Puzzle => {
+            let p = Puzzles::load(args.puzzles())?;
+            if p.is_empty() {
+                return Err(Error::NoPuzzles);
+            }
+            puzzles = Some(p);
+        }
+        Command::Tutorial => tutorial = Some(Tutorial::default()),
+        Command::BuildPuzzles(dir) => {
+            let mut p = Puzzles::load(args.puzzles())?;
+            let cnt = p.len();
+            p.build(dir, args.color())?;
+            let file = p.save(args.puzzles())?;
+            printmcln!(
+                args.color(),
+                "Added {} puzzles to {'i}{}{'_}.",
+                p.len() - cnt,
+                file.display()
+            );
+            return Ok(());
+        }
+        Command::Stats => {
+            Stats::load()?.print(args.color());
+            return Ok(());
+        }
+        Command::Analyze(file) => return analyze::analyze(file, args.color()),
+        Command::SelfPlay => return selfplay::selfplay(&args),
+        Command::Tournament => return tournament::tournament(&args),
+        Command::BuildBook(dir) => {
+            let book = Book::build(dir, args.color())?;
+            let file = book.save(args.book())?;
+            printmcln!(
+                args.color(),
+                "Saved {} positions to {'i}{}{'_}.",
+                book.len(),
+                file.display()
+            );
+            return Ok(());
+        }
+    }
 
-    let board = Board::new(args.size(), args.win_len());
-    let mut mainloop = Mainloop::new(board, args.color());
-    mainloop.run()?;
+    let board = match (&puzzles, &tutorial) {
+        (Some(p), _) => p.current().board()?,
+        (_, Some(t)) => t.board()?,
+        _ => Board::new(args.position(), args.win_len())?,
+    };
 
-    Mainloop::restore()
+    // The terminal must be restored even if the game fails.
+    let res = Mainloop::prepare().and_then(|_| {
+        let mut mainloop = Mainloop::new(board, &args)?;
+        if let Some(p) = puzzles {
+            mainloop.set_puzzles(p);
+        }
+        if let Some(t) = tutorial {
+            mainloop.set_tutorial(t);
+        }
+        mainloop.run()
+    });
+    Mainloop::restore()?;
+    res
 }
 
 fn help(args: &Args) {
@@ -60,6 +146,36 @@ fn help(args: &Args) {
         "BonnyAD9".into()
     };
 
+    let keymap = args.keymap();
+    let reset = keymap.format_keys(Action::Reset);
+    let zoom_in = keymap.format_keys(Action::ZoomIn);
+    let zoom_out = keymap.format_keys(Action::ZoomOut);
+    let labels = keymap.format_keys(Action::ToggleLabels);
+    let numbers = keymap.format_keys(Action::ToggleNumbers);
+    let controls: String = Action::ALL
+        .iter()
+        .map(|a| {
+            formatmc!(
+                color,
+                "  {'b}[{}]{'_}\n    {}\n\n",
+                keymap.format_keys(*a),
+                a.description()
+            )
+        })
+        .collect();
+    let controls = controls.trim_end();
+    let mut actions = String::new();
+    let mut line = String::new();
+    for a in Action::ALL {
+        if line.len() + a.name().len() > 72 {
+            actions += &format!("   {line}\n");
+            line.clear();
+        }
+        line += " ";
+        line += a.name();
+    }
+    actions += &format!("   {line}");
+
     printmcln!(
         color,
         "Welcome in {'i g}ttoe{'_} by {signature}{'_}
@@ -67,6 +183,76 @@ Version {v}
 
 {'g}Usage:
   {'c}ttoe {'gr}[flags]
+  {'c}ttoe {'b}stats {'gr}[flags]
+  {'c}ttoe {'b}analyze {'w}<file> {'gr}[flags]
+  {'c}ttoe {'b}book build {'w}<games-dir> {'gr}[flags]
+  {'c}ttoe {'b}selfplay {'gr}[flags]
+  {'c}ttoe {'b}tournament {'gr}[flags]
+  {'c}ttoe {'b}puzzle {'gr}[flags]
+  {'c}ttoe {'b}tutorial {'gr}[flags]
+  {'c}ttoe {'b}puzzle build {'w}<games-dir> {'gr}[flags]
+
+{'g}Commands:
+  {'b}stats{'_}
+    Show statistics and elo ratings of the named players.
+
+  {'b}analyze {'w}<file>{'_}
+    Show forced wins by VCF (victory by continuous fours) and likely wins by
+    VCT (victory by continuous threats, not all the defences are checked)
+    found for either player after each move of the saved game in {'w}<file>{'_}.
+
+  {'b}book build {'w}<games-dir>{'_}
+    Create opening book from the first moves of all the saved games in
+    {'w}<games-dir>{'_}. Moves of winners have larger weight. The book is saved to
+    the data directory or to the file given by {'y}--book{'_}.
+
+  {'b}selfplay{'_}
+    Play {'y}-n{'_} games between the computer players given by {'y}--ai{'_} without the
+    user interface and show how many games each of them won. The players
+    alternate the colors. Missing player is {'i}minimax{'_}.
+
+  {'b}tournament{'_}
+    Play tournament between the engines given by {'y}--engine{'_}. Each pair of
+    engines plays {'y}-n{'_} games (2 by default) with alternating colors. The
+    crosstable and all the games are saved to the directory given by {'y}-o{'_}.
+
+  {'b}puzzle{'_}
+    Solve puzzles: find forced win in the given number of moves. The
+    computer defends. Puzzles are loaded from the data directory or from the
+    file given by {'y}--puzzles{'_}.
+
+  {'b}puzzle build {'w}<games-dir>{'_}
+    Find puzzles in the saved games in {'w}<games-dir>{'_} (e.g. from {'b}selfplay{'_})
+    and add them to the puzzles file.
+
+  {'b}tutorial{'_}
+    Learn the basics of gomoku: winning, blocking and making threats. Each
+    step shows instructions and checks your move.
+
+{'g}Saved games:
+  Saved game is text file with one item per line. For example:
+    {'i}size 15x15{'_}
+    {'i}win 5{'_}
+    {'i}moves H8 J9 H9{'_}
+  {'i}start <position>{'_} may be used instead of {'i}size{'_} to start from position in
+  the same format as in {'y}--position{'_}. Moves are in the gomoku notation:
+  columns are letters ({'i}I{'_} is skipped) and rows are numbers from the bottom.
+
+{'g}Config:
+  Defaults of the flags may be set in {'i}config.ini{'_} in the config directory
+  ({'i}~/.config/ttoe{'_}) or in the file given by {'y}--config{'_}. Each line sets
+  one flag by its long name, flags without value are set with {'i}true{'_} or
+  {'i}false{'_}. Flags given on the command line take precedence and flags without
+  value may be turned off with {'y}--no-<flag>{'_}, e.g. {'y}--no-threats{'_}. Keys are bound in the section
+  {'i}[keys]{'_} by the action name and comma separated keys. Action names are:
+{actions}
+  For example:
+    {'i}size = 19x19{'_}
+    {'i}color = always{'_}
+    {'i}threats = true{'_}
+    {'i}[keys]{'_}
+    {'i}left = Left, h{'_}
+    {'i}help = ?{'_}
 
 {'g}Flags:
   {'y}-h  -?  --help{'_}
@@ -79,46 +265,157 @@ Version {v}
     Set the number of same cells needed to win. The default is {'i}5{'_} or the
     larger of the board dimensions.
 
+  {'y}-m  --match {'w}<games>{'_}
+    Play best of {'w}<games>{'_} match. The score is tracked across the games,
+    players alternate in who starts and {'b}[{reset}]{'_} starts the next game.
+
+  {'y}--x-player  --o-player {'w}<name>{'_}
+    Name the players. Wins, losses, draws and elo rating of named players
+    are stored and can be shown with {'b}ttoe stats{'_}.
+
+  {'y}-p  --position {'w}<position or file>{'_}
+    Start the game from the given position. It may be given directly or as
+    path to file that contains it. Each row is on separate line or separated
+    with {'i}/{'_}. Empty cell is {'i}.{'_}, {'i}x{'_} and {'i}o{'_} are the players. The position may
+    be prefixed with {'i}x:{'_} or {'i}o:{'_} to set who is on turn. By default {'i}o{'_} is on
+    turn if there are more {'i}x{'_} and {'i}x{'_} otherwise. The position also sets the
+    board size. For example {'i}o:...../..x../.x.x./...../.....{'_}
+
+  {'y}--threats{'_}
+    Show the threats on the board. {'i}!{'_} marks cells where the player can win
+    with single move and {'i}*{'_} ({'i}•{'_} with {'y}--unicode{'_}) marks cells where
+    the player can create two such cells in line, e.g. open line of length one
+    less than the win length. Lines with gaps such as {'i}XX.XX{'_} are also
+    counted. The marks have the color of the player.
+
+  {'y}--ai {'w}(x|o)[:<engine>]{'_}
+    Let the computer play for the given player. May be used twice to let the
+    computer play against itself. The engine may be:
+      {'i}minimax[:<time>]{'_} (default) heuristic search.
+      {'i}mcts[:<playouts>]{'_} Monte Carlo tree search that plays random games.
+      {'i}ext:<path>{'_} external engine with the Piskvork protocol (only with win
+        length 5).
+      {'i}easy{'_}, {'i}medium{'_}, {'i}hard{'_} difficulty levels ({'i}mcts:1000{'_},
+        {'i}minimax:200ms{'_} and {'i}minimax{'_}).
+
+  {'y}--move-time {'w}<time>{'_}
+    Time limit for each move of the computer. Default is {'i}1s{'_}. External
+    engine that doesn't play in time loses.
+
+  {'y}--delay {'w}<time>{'_}
+    Time before each move of the computer, e.g. {'i}1s{'_} or {'i}200ms{'_}. Default
+    is {'i}500ms{'_} if the computer plays against itself and no delay otherwise.
+
+  {'y}-n  --games {'w}<N>{'_}
+    Number of games played by {'b}selfplay{'_} (100 by default) or by each pair
+    in {'b}tournament{'_} (2 by default).
+
+  {'y}-e  --engine {'w}<engine>{'_}
+    Add engine to {'b}tournament{'_}. The engine is in the same format as in
+    {'y}--ai{'_}.
+
+  {'y}--gauntlet{'_}
+    The first engine of {'b}tournament{'_} plays against all the others instead
+    of playing round robin.
+
+  {'y}-o  --out {'w}<dir>{'_}
+    Directory for the games and the crosstable of {'b}tournament{'_} (default is
+    {'i}tournament{'_}) or for the games of {'b}selfplay{'_}.
+
+  {'y}--puzzles {'w}<file>{'_}
+    Puzzles file used instead of the one in the data directory.
+
+  {'y}--playouts {'w}<N>{'_}
+    Number of random games that {'i}mcts{'_} plays for each move. Default is
+    5000.
+
+  {'y}--seed {'w}<N>{'_}
+    Seed for {'i}mcts{'_} and the opening book. Games with the same seed and
+    the same moves of the player are the same if {'i}mcts{'_} finishes all its
+    playouts within {'y}--move-time{'_}, so use less {'y}--playouts{'_} or longer
+    {'y}--move-time{'_} for reproducible games.
+
+  {'y}--book {'w}<file>{'_}
+    Opening book used by the computer player instead of the one in the data
+    directory.
+
+  {'y}--theme {'w}(default|colorblind|high-contrast|mono){'_}
+    Colors of the players and of the grid and the style of the cursor.
+    {'i}colorblind{'_} uses blue and orange, {'i}high-contrast{'_} uses cyan and yellow
+    with brighter grid and {'i}mono{'_} has no player colors. The last two
+    also mark the selected cell with brackets.
+
+  {'y}--symbols {'w}<x><o>{'_}
+    Symbols used for the players on the board, e.g. {'i}@O{'_}. Default is {'i}XO{'_}.
+
+  {'y}--unicode{'_}
+    Draw the board with unicode box drawing characters and use {'i}✕{'_} and {'i}○{'_}
+    as the default symbols. Stones may be used with {'y}--symbols {'i}●○{'_}.
+
+  {'y}--labels{'_}
+    Show the coordinates around the board: columns are letters ({'i}I{'_} is
+    skipped) and rows are numbers from the bottom. Toggle in game with {'b}[{labels}]{'_}.
+    The coordinate of the cursor is always shown below the board.
+
+  {'y}--move-numbers{'_}
+    Show the order of the moves instead of {'i}X{'_} and {'i}O{'_}. The last move is
+    always highlighted. Toggle in game with {'b}[{numbers}]{'_}.
+
+  {'y}--layout {'w}(4x2|3x1|2x1){'_}
+    Number of characters taken by each cell. {'i}4x2{'_} (default) draws the full grid,
+    {'i}3x1{'_} only horizontal grid lines and {'i}2x1{'_} no grid. Smaller cells fit
+    larger boards. The layout can be changed in game with {'b}[{zoom_in}]{'_} and {'b}[{zoom_out}]{'_}.
+
+  {'y}--config {'w}<file>{'_}
+    Config file used instead of the one in the config directory.
+
   {'y}--color  --colour {'w}(auto|always|never){'_}
     Determines whether color should be used.
 
-{'g}In game controls:
-  {'b}[Arrows/wasd]{'_}
-    Move the selected cell (cursor).
-
-  {'b}[Enter/Space/0]{'_}
-    Play at the selected cell (cursor).
+  {'y}-t  --time {'w}<time>[+<increment>]{'_}
+  {'y}-t  --time {'w}<time>+<periods>x<period>{'_}
+    Play with chess clock. Each player has {'w}<time>{'_} for the whole game.
+    With {'w}<increment>{'_} (Fischer) the player gets the extra time after
+    each move. With byo-yomi the player has {'w}<periods>{'_} periods of
+    {'w}<period>{'_} time after the main time runs out. Each move must fit
+    into single period. Times are given as for example {'i}90{'_}, {'i}30s{'_},
+    {'i}5m{'_} or {'i}1h30m{'_}. The player whose time runs out loses.
 
-  {'b}[u]{'_}
-    Undo last turn. This can undo only 1 last turn.
+{'g}In game controls:
+{controls}
 
-  {'b}[r]{'_}
-    Reset. Start a new game.
+{'g}In game commands:{'_}
+  Commands are typed after {'b}[{}]{'_}. {'b}[Tab]{'_} completes the command name and
+  {'b}[Up]{'_}/{'b}[Down]{'_} browse the previous commands.
 
-  {'b}[q]{'_}
-    Quit
+  {'b}save {'w}<file>{'_}
+    Save the game to {'w}<file>{'_}. It may be loaded with {'b}load{'_} or analyzed with
+    {'c}ttoe {'b}analyze{'_}.
 
-  {'b}[Shift+Arrows/WASD]{'_}
-    Move the cursor in the given direction to the first empty space after
-    occupated space.
+  {'b}load {'w}<file>{'_}
+    Load the saved game.
 
-  {'b}[c]{'_}
-    Move cursor to the center of the board.
+  {'b}new {'gr}[{'w}<width>x<height>{'gr}] [{'w}<length>{'gr}] [{'w}x{'gr}|{'w}o{'gr}] [{'w}freestyle{'gr}]{'_}
+    Start new game with the given size, win length and starting player in
+    any order. Values that are not given are the same as in the current
+    game. The win length must fit the board.
 
-  {'b}[Ctrl+Arrows/Ctrl+wasd]{'_}
-    Scroll (when the board doesn't fit the terminal).
+  {'b}size {'w}<width>x<height>{'_}
+    Start new game on board of the given size.
 
-  {'b}[Alt+c]{'_}
-    Toggle color.
+  {'b}win {'w}<length>{'_}
+    Start new game with the given number of stones in line needed to win.
 
-  {'b}[C]{'_}
-    Clear the last message.
+  {'b}undo {'gr}[{'w}<count>{'gr}]{'_}
+    Undo the given number of moves (1 by default).
 
-  {'b}[Ctrl+c]{'_}
-    Rage quit.
+  {'b}rules {'gr}[{'w}<rules>{'gr}]{'_}
+    Show or set the rules. Only freestyle (line of at least the win length
+    wins) is supported, other rules such as {'i}renju{'_} are rejected.
 
-  {'b}[h]{'_}
-    Show short help.
-",
+  {'b}ai {'w}(x|o) {'gr}[{'w}<engine>{'gr}|{'w}off{'gr}]{'_}
+    Let the computer play for {'i}x{'_} or {'i}o{'_} with the {'w}<engine>{'_} as for {'y}--ai{'_}.
+    {'i}off{'_} lets human play instead.",
+        keymap.format_keys(Action::Command),
     )
 }
@@ -1,28 +1,43 @@
 use std::{
     io::{self, Write},
-    time::Duration,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
 use termal::{
     codes, formatc, printc,
-    raw::{
-        self,
-        events::{Event, KeyCode, Modifiers},
-        Terminal,
-    },
+    raw::{self, events::Event, Terminal},
 };
 
 use crate::{
+    ai::{Ai, AiKind},
+    analyzer::Analyzer,
+    args::Args,
     board::Board,
     board_gui::GuiState,
+    book::Book,
+    clock::Clock,
+    command::Command,
+    coord,
     draw_buffer::DrawBuffer,
+    engine::Engine,
     err::{Error, Result},
+    game::Game,
+    keymap::{Action, Keymap},
+    layout::Layout,
+    match_score::MatchScore,
+    new_game::NewGame,
+    position::Position,
+    prompt::{Input, Prompt},
+    puzzle::{Progress, Puzzles},
+    stats::Stats,
     suit::Suit,
+    tutorial::Tutorial,
     vec2::Vec2,
 };
 
-const DEFAULT_MSG: &str = "\x1b[90mPress [h] to show help.";
 const WAIT_TIME: Duration = Duration::from_millis(100);
+const HINT_TIME: Duration = Duration::from_secs(1);
 
 pub struct Mainloop {
     board: Board,
@@ -34,21 +49,98 @@ pub struct Mainloop {
     redraw: bool,
     size: Vec2,
     gui_state: GuiState,
+    clock: Option<Clock>,
+    clock_str: String,
+    score: Option<MatchScore>,
+    players: Option<(String, String)>,
+    /// The game has ended. Its moves can't be undone because the result is
+    /// already recorded to the match score and stats.
+    game_over: bool,
+    analyzer: Option<Analyzer>,
+    x_ai: Option<Ai>,
+    o_ai: Option<Ai>,
+    /// Settings of computer players created in game.
+    playouts: usize,
+    move_time: Duration,
+    seed: Option<u64>,
+    book: Option<PathBuf>,
+    /// Minimal time before the computer plays.
+    delay: Duration,
+    last_move: Instant,
+    puzzles: Option<Puzzles>,
+    tutorial: Option<Tutorial>,
+    keymap: Keymap,
+    default_msg: String,
+    prompt: Option<(PromptKind, Prompt)>,
+    /// Previously typed commands.
+    history: Vec<String>,
+}
+
+/// What is typed to the prompt.
+#[derive(Clone, Copy)]
+enum PromptKind {
+    Goto,
+    Command,
+    /// Step of the new game dialog and the settings chosen so far.
+    NewGame(usize, NewGame),
 }
 
 impl Mainloop {
-    pub fn new(board: Board, color: bool) -> Self {
-        Self {
+    pub fn new(board: Board, args: &Args) -> Result<Self> {
+        let on_turn = board.on_turn();
+        let mut gui_state = GuiState::default();
+        gui_state.set_threats(args.threats());
+        gui_state.set_theme(args.theme());
+        gui_state.set_unicode(args.unicode());
+        gui_state.set_layout(args.layout());
+        gui_state.set_labels(args.labels());
+        gui_state.set_move_numbers(args.move_numbers());
+        let clock = args.time().map(|t| Clock::new(t, on_turn));
+        let (x_ai, o_ai) = Ai::from_args(args)?;
+        Ok(Self {
             board,
             terminal: Terminal::new(),
             out: DrawBuffer::new(),
             msg: String::new(),
             persistant_msg: String::new(),
-            color,
+            color: args.color(),
             redraw: true,
             size: (0, 0).into(),
-            gui_state: GuiState::default(),
-        }
+            gui_state,
+            clock,
+            clock_str: String::new(),
+            score: args.match_games().map(|g| MatchScore::new(g, on_turn)),
+            players: args.players().map(|(x, o)| (x.to_owned(), o.to_owned())),
+            game_over: false,
+            analyzer: None,
+            x_ai,
+            o_ai,
+            playouts: args.playouts(),
+            move_time: args.move_time(),
+            seed: args.seed(),
+            book: args.book().map(|b| b.to_owned()),
+            delay: args.delay(),
+            last_move: Instant::now(),
+            puzzles: None,
+            tutorial: None,
+            keymap: args.keymap().clone(),
+            prompt: None,
+            history: vec![],
+            default_msg: format!(
+                "\x1b[90mPress [{}] to show help.",
+                args.keymap().format_keys(Action::Help)
+            ),
+        })
+    }
+
+    /// Switches to the puzzle mode and starts the current puzzle. The
+    /// computer defends against the player. Puzzles don't count to the match
+    /// score and stats.
+    pub fn set_puzzles(&mut self, puzzles: Puzzles) {
+        self.score = None;
+        self.players = None;
+        self.puzzles = Some(puzzles);
+        self.start_puzzle();
     }
 
     pub fn prepare() -> Result<()> {
@@ -67,6 +159,10 @@ impl Mainloop {
 
     pub fn iterate(&mut self) -> Result<bool> {
         self.fetch_size()?;
+        self.update_clock();
+        if self.analyzer.as_mut().is_some_and(|a| a.poll()) {
+            self.redraw = true;
+        }
 
         if self.redraw {
             self.draw();
@@ -74,6 +170,18 @@ impl Mainloop {
 
         self.update_msg();
 
+        // Keys have priority so that the game may be quit while the
+        // computer is on turn.
+        if self.ai_on_turn()
+            && self.last_move.elapsed() >= self.delay
+            && !self.terminal.has_buffered_input()
+            && !raw::wait_for_stdin(Duration::ZERO)?
+        {
+            self.ai_play();
+            self.redraw = true;
+            return Ok(true);
+        }
+
         if !self.has_input()? {
             return Ok(true);
         }
@@ -99,10 +207,18 @@ impl Mainloop {
     }
 
     fn draw(&mut self) {
-        let msg = [self.msg.as_str(), &self.persistant_msg]
+        let prompt = self.prompt.as_ref().map(|(_, p)| p.format());
+        let msg = [prompt.as_deref().unwrap_or_default(), &self.msg]
             .into_iter()
+            .chain([self.persistant_msg.as_str()])
             .find(|a| !a.is_empty())
-            .unwrap_or(DEFAULT_MSG);
+            .unwrap_or(&self.default_msg);
+        let mut status = self.status();
+        if !status.is_empty() {
+            status += "  ";
+        }
+        status += msg;
+        let msg = status.as_str();
 
         self.out.set_base((1, 1));
         self.board
@@ -137,65 +253,81 @@ impl Mainloop {
             return Ok(true);
         };
 
-        match key.code {
-            KeyCode::Up | KeyCode::Char('w') => {
-                self.move_dir((0, -1), key.modifiers);
-            }
-            KeyCode::Left | KeyCode::Char('a') => {
-                self.move_dir((-1, 0), key.modifiers);
-            }
-            KeyCode::Down | KeyCode::Char('s') => {
-                self.move_dir((0, 1), key.modifiers);
-            }
-            KeyCode::Right | KeyCode::Char('d') => {
-                self.move_dir((1, 0), key.modifiers);
-            }
-            KeyCode::Enter | KeyCode::Space | KeyCode::Char('0') => {
-                self.play();
-            }
-            KeyCode::Char('u') => {
-                self.board.undo();
-            }
-            KeyCode::Char('r') => {
-                self.reset();
+        if let Some((kind, prompt)) = &mut self.prompt {
+            let kind = *kind;
+            match prompt.key(&key) {
+                Input::Edit => {}
+                Input::Submit(text) => {
+                    self.prompt = None;
+                    match kind {
+                        PromptKind::Goto => self.goto(&text),
+                        PromptKind::Command => self.command(text),
+                        PromptKind::NewGame(step, game) => {
+                            self.new_game_step(step, game, &text)
+                        }
+                    }
+                }
+                Input::Cancel => self.prompt = None,
             }
-            KeyCode::Char('q') => {
-                return Ok(false);
+            self.redraw = true;
+            return Ok(true);
+        }
+
+        let Some(action) = self.keymap.action(&key) else {
+            return Ok(true);
+        };
+
+        match action {
+            Action::Up => self.move_by((0, -1)),
+            Action::Left => self.move_by((-1, 0)),
+            Action::Down => self.move_by((0, 1)),
+            Action::Right => self.move_by((1, 0)),
+            Action::JumpUp => self.shift_move((0, -1)),
+            Action::JumpLeft => self.shift_move((-1, 0)),
+            Action::JumpDown => self.shift_move((0, 1)),
+            Action::JumpRight => self.shift_move((1, 0)),
+            Action::ScrollUp => self.gui_state.scroll_by((0, -1)),
+            Action::ScrollLeft => self.gui_state.scroll_by((-1, 0)),
+            Action::ScrollDown => self.gui_state.scroll_by((0, 1)),
+            Action::ScrollRight => self.gui_state.scroll_by((1, 0)),
+            Action::Play => self.play(),
+            Action::Undo => self.undo(),
+            Action::Reset => self.reset(),
+            Action::Next => self.next(),
+            Action::ZoomIn => self.zoom(self.gui_state.layout().zoom_in()),
+            Action::ZoomOut => self.zoom(self.gui_state.layout().zoom_out()),
+            Action::ToggleLabels => self.toggle_labels(),
+            Action::ToggleNumbers => self.toggle_move_numbers(),
+            Action::Goto => {
+                self.prompt = Some((PromptKind::Goto, Prompt::new("Go to: ")))
             }
-            KeyCode::Char('c') => {
-                if key.modifiers.contains(Modifiers::CONTROL) {
-                    return Err(Error::RageQuit);
-                } else if key.modifiers.contains(Modifiers::SHIFT) {
-                    self.persistant_msg.clear();
-                } else if key.modifiers.contains(Modifiers::ALT) {
-                    self.toggle_color();
-                } else {
-                    self.board.set_selected((self.board.size() - (1, 1)) / 2);
-                    self.gui_state.center();
-                }
+            Action::Command => {
+                let mut prompt = Prompt::new(":");
+                prompt.set_history(self.history.clone());
+                prompt.set_completions(&Command::NAMES);
+                self.prompt = Some((PromptKind::Command, prompt));
             }
-            KeyCode::Char('h') => {
-                self.show_help();
+            Action::NewGame => {
+                self.ask_new_game(0, NewGame::from_board(&self.board))
             }
-            _ => {
-                return Ok(true);
+            Action::Quit => return Ok(false),
+            Action::RageQuit => return Err(Error::RageQuit),
+            Action::ClearMsg => self.persistant_msg.clear(),
+            Action::ToggleColor => self.toggle_color(),
+            Action::Center => {
+                self.board.set_selected((self.board.size() - (1, 1)) / 2);
+                self.gui_state.center();
             }
+            Action::ToggleThreats => self.toggle_threats(),
+            Action::ToggleEval => self.toggle_eval(),
+            Action::Hint => self.hint(),
+            Action::Help => self.show_help(),
         }
 
         self.redraw = true;
         Ok(true)
     }
 
-    fn move_dir(&mut self, dir: impl Into<Vec2<isize>>, m: Modifiers) {
-        if m.contains(Modifiers::SHIFT) {
-            self.shift_move(dir);
-        } else if m.contains(Modifiers::CONTROL) {
-            self.gui_state.scroll_by(dir);
-        } else {
-            self.move_by(dir);
-        }
-    }
-
     fn move_by(&mut self, dif: impl Into<Vec2<isize>>) {
         self.board.set_selected(
             self.board.selected().saturating_add_signed(dif.into()),
@@ -230,25 +362,343 @@ impl Mainloop {
     }
 
     fn play(&mut self) {
+        let on_turn = self.board.on_turn();
+        if on_turn.is_none() {
+            return;
+        }
+
         if let Err(e) = self.board.play() {
             self.msg += &formatc!("{'r}{e}{'_}");
+            return;
+        }
+        if on_turn != self.board.on_turn() {
+            if let Some(c) = &mut self.clock {
+                c.switch(self.board.on_turn());
+            }
         }
+
+        self.board_changed();
+
         match self.board.check_win() {
-            None => {
-                self.set_persistant_msg(formatc!("{'_}Draw!"));
+            None => self.end_game(Suit::None, String::new()),
+            Some(Suit::None) => {}
+            Some(winner) => self.end_game(winner, String::new()),
+        }
+
+        self.check_puzzle();
+        self.check_tutorial();
+    }
+
+    /// Switches to the tutorial mode and starts the current step. The
+    /// tutorial doesn't count to the match score and stats.
+    pub fn set_tutorial(&mut self, tutorial: Tutorial) {
+        self.score = None;
+        self.players = None;
+        self.tutorial = Some(tutorial);
+        self.start_tutorial_step();
+    }
+
+    fn start_tutorial_step(&mut self) {
+        let Some(tutorial) = &self.tutorial else {
+            return;
+        };
+        match tutorial.board() {
+            Ok(b) => {
+                self.board = b;
+                self.game_over = false;
             }
-            Some(Suit::Circle) => {
-                self.set_persistant_msg(formatc!("{'r}O {'_}Wins!\r"));
+            Err(e) => {
+                self.msg += &formatc!("{'r}Invalid tutorial step: {e}{'_}");
+                return;
+            }
+        }
+        self.show_tutorial_task();
+        self.board_changed();
+    }
+
+    fn show_tutorial_task(&mut self) {
+        if let Some(t) = &self.tutorial {
+            self.set_persistant_msg(t.task());
+        }
+    }
+
+    /// Checks whether the player played the expected move in the tutorial.
+    /// Wrong move is taken back.
+    fn check_tutorial(&mut self) {
+        let Some(tutorial) = &self.tutorial else {
+            return;
+        };
+        let Some((correct, msg)) = tutorial.check(&self.board) else {
+            return;
+        };
+
+        if correct {
+            let next = if tutorial.is_last() {
+                let quit = self.keymap.format_keys(Action::Quit);
+                formatc!(
+                    "You know the basics! Press {'b}[{quit}]{'_} to quit."
+                )
+            } else {
+                let next = self.keymap.format_keys(Action::Next);
+                formatc!("Press {'b}[{next}]{'_} to continue.")
+            };
+            self.board.inspect_mode();
+            self.set_persistant_msg(formatc!(
+                "{'g}Correct!{'_} {msg} {next}\r"
+            ));
+        } else {
+            // Wrong move may also end the game.
+            self.game_over = false;
+            self.undo_move();
+            self.set_persistant_msg(formatc!("{'r}Not quite.{'_} {msg}\r"));
+        }
+        self.board_changed();
+    }
+
+    fn next(&mut self) {
+        if let Some(p) = &mut self.puzzles {
+            p.next();
+            self.start_puzzle();
+        } else if let Some(t) = &mut self.tutorial {
+            if t.next() {
+                self.start_tutorial_step();
             }
-            Some(Suit::Cross) => {
-                self.set_persistant_msg(formatc!("{'b}X {'_}Wins!\r"));
+        }
+    }
+
+    fn start_puzzle(&mut self) {
+        let Some(puzzles) = &self.puzzles else {
+            return;
+        };
+        let puzzle = puzzles.current();
+        match puzzle.board() {
+            Ok(b) => {
+                self.board = b;
+                self.game_over = false;
             }
-            _ => {
+            Err(e) => {
+                self.msg += &formatc!("{'r}Invalid puzzle: {e}{'_}");
                 return;
             }
         }
 
+        // Minimax never fails to start.
+        let defender =
+            Ai::new(AiKind::default(), 1, HINT_TIME, None, Book::default())
+                .ok();
+        (self.x_ai, self.o_ai) = match puzzle.solver() {
+            Suit::Circle => (defender, None),
+            _ => (None, defender),
+        };
+
+        if let Some(c) = &mut self.clock {
+            c.reset(self.board.on_turn());
+        }
+        self.show_puzzle_task();
+        self.board_changed();
+    }
+
+    fn show_puzzle_task(&mut self) {
+        let Some(puzzles) = &self.puzzles else {
+            return;
+        };
+        let msg = format!(
+            "{} (puzzle {}/{})",
+            puzzles.current().task(),
+            puzzles.index() + 1,
+            puzzles.len()
+        );
+        self.set_persistant_msg(msg);
+    }
+
+    /// Checks whether the player solved or failed the puzzle.
+    fn check_puzzle(&mut self) {
+        let Some(puzzles) = &self.puzzles else {
+            return;
+        };
+        let puzzle = puzzles.current();
+        let on_turn = self.board.on_turn();
+        // Wait for the reply of the defender.
+        if !on_turn.is_none() && on_turn != puzzle.solver() {
+            return;
+        }
+
+        let next = self.keymap.format_keys(Action::Next);
+        match puzzle.progress(&self.board) {
+            Progress::Solved => self.set_persistant_msg(formatc!(
+                "{'g}Solved!{'_} Press {'b}[{next}]{'_} for next puzzle.\r"
+            )),
+            Progress::Failed => {
+                let undo = if self.game_over {
+                    String::new()
+                } else {
+                    let undo = self.keymap.format_keys(Action::Undo);
+                    formatc!("{'b}[{undo}]{'_} to undo, ")
+                };
+                let reset = self.keymap.format_keys(Action::Reset);
+                self.set_persistant_msg(formatc!(
+                    "{'r}Failed.{'_} Press {undo}{'b}[{reset}]{'_} to retry \
+                    or {'b}[{next}]{'_} for next puzzle.\r"
+                ))
+            }
+            Progress::Playing => {}
+        }
+    }
+
+    fn end_game(&mut self, winner: Suit, mut msg: String) {
+        self.game_over = true;
         self.board.inspect_mode();
+        self.board_changed();
+        if let Some(c) = &mut self.clock {
+            c.stop();
+        }
+
+        msg += &match winner {
+            Suit::None => formatc!("{'_}Draw!"),
+            Suit::Circle => formatc!("{'r}O {'_}Wins!"),
+            Suit::Cross => formatc!("{'b}X {'_}Wins!"),
+        };
+
+        if let Some(score) = &mut self.score {
+            score.record(winner);
+            msg += &match score.winner() {
+                None => formatc!(
+                    " Press {'b}[{}]{'_} for next game.",
+                    self.keymap.format_keys(Action::Reset)
+                ),
+                Some(Suit::None) => formatc!(" The match is drawn."),
+                Some(Suit::Circle) => formatc!(" {'r}O {'_}wins the match!"),
+                Some(Suit::Cross) => formatc!(" {'b}X {'_}wins the match!"),
+            };
+        }
+
+        msg.push('\r');
+        self.set_persistant_msg(msg);
+
+        if let Err(e) = self.record_stats(winner) {
+            self.msg += &formatc!("{'r}Failed to save stats: {e}{'_}");
+        }
+    }
+
+    fn record_stats(&self, winner: Suit) -> Result<()> {
+        let Some((x, o)) = &self.players else {
+            return Ok(());
+        };
+
+        let mut stats = Stats::load()?;
+        stats.record(x, o, winner);
+        stats.save()
+    }
+
+    fn undo(&mut self) {
+        if !self.can_undo() {
+            return;
+        }
+        // Undo also the moves of the computer so that the player is on turn
+        // again.
+        while self.undo_move() && self.ai_on_turn() {}
+        self.show_puzzle_task();
+        self.board_changed();
+    }
+
+    /// Checks whether moves may be undone. Shows message if they can't.
+    fn can_undo(&mut self) -> bool {
+        if self.game_over {
+            self.msg += &formatc!(
+                "{'r}The game is over. Press [{}] for new game.{'_}",
+                self.keymap.format_keys(Action::Reset)
+            );
+        }
+        !self.game_over
+    }
+
+    /// Undoes the last move together with the clock. Returns `false` if
+    /// there is no move to undo.
+    fn undo_move(&mut self) -> bool {
+        if self.board.moves().is_empty() {
+            return false;
+        }
+        self.board.undo();
+        if let Some(c) = &mut self.clock {
+            c.undo(self.board.on_turn());
+        }
+        true
+    }
+
+    fn ai_mut(&mut self, suit: Suit) -> Option<&mut Ai> {
+        match suit {
+            Suit::Cross => self.x_ai.as_mut(),
+            Suit::Circle => self.o_ai.as_mut(),
+            Suit::None => None,
+        }
+    }
+
+    fn ai_on_turn(&mut self) -> bool {
+        self.ai_mut(self.board.on_turn()).is_some()
+    }
+
+    fn ai_play(&mut self) {
+        let board = self.board.clone();
+        let suit = board.on_turn();
+        let Some(ai) = self.ai_mut(suit) else {
+            return;
+        };
+        match ai.best_move(&board) {
+            Ok(Some(pos)) => {
+                self.board.set_selected(pos);
+                self.play();
+            }
+            Ok(None) => {}
+            // The player that fails loses.
+            Err(e) => {
+                let msg = match suit {
+                    Suit::Circle => formatc!("{'r}O {'_}forfeits: {e} "),
+                    _ => formatc!("{'b}X {'_}forfeits: {e} "),
+                };
+                self.end_game(suit.oposite(), msg);
+            }
+        }
+    }
+
+    fn board_changed(&mut self) {
+        self.last_move = Instant::now();
+        if let Some(a) = &mut self.analyzer {
+            a.start(&self.board);
+        }
+    }
+
+    fn update_clock(&mut self) {
+        let Some(clock) = &self.clock else {
+            return;
+        };
+
+        let flagged = clock.flagged();
+        if let Some(suit) = flagged {
+            let msg = match suit {
+                Suit::Circle => formatc!("{'r}O {'_}ran out of time. "),
+                _ => formatc!("{'b}X {'_}ran out of time. "),
+            };
+            self.end_game(suit.oposite(), msg);
+        }
+
+        let Some(clock) = &self.clock else {
+            return;
+        };
+        let clock_str = clock.format();
+        if clock_str != self.clock_str || flagged.is_some() {
+            self.clock_str = clock_str;
+            self.redraw = true;
+        }
+    }
+
+    fn hint(&mut self) {
+        match Engine::best_move(&self.board, HINT_TIME) {
+            Some(pos) => {
+                self.board.set_selected(pos);
+                self.msg += "Hint: play at the selected cell.";
+            }
+            None => self.msg += "There is no move to hint.",
+        }
     }
 
     fn set_persistant_msg(&mut self, s: impl AsRef<str>) {
@@ -256,9 +706,240 @@ impl Mainloop {
         self.persistant_msg += s.as_ref();
     }
 
+    fn status(&self) -> String {
+        [
+            coord::format(self.board.selected(), self.board.size()),
+            self.score.as_ref().map(|s| s.format()).unwrap_or_default(),
+            self.clock_str.clone(),
+            self.analyzer
+                .as_ref()
+                .map(|a| a.format())
+                .unwrap_or_default(),
+        ]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("  ")
+    }
+
     fn reset(&mut self) {
         self.persistant_msg.clear();
-        self.board.reset();
+        self.game_over = false;
+        match &mut self.score {
+            Some(score) => {
+                // Only finished game counts. Otherwise just restart the
+                // current game.
+                if score.winner().is_some() {
+                    score.reset();
+                }
+                self.board.reset_with(score.starts());
+            }
+            None => self.board.reset(),
+        }
+        if let Some(c) = &mut self.clock {
+            c.reset(self.board.on_turn());
+        }
+        self.show_puzzle_task();
+        self.show_tutorial_task();
+        self.board_changed();
+    }
+
+    /// Moves the cursor to the coordinate such as `K10` or `10,12`.
+    fn goto(&mut self, text: &str) {
+        let size = self.board.size();
+        let text = text.trim();
+        let Some(pos) = coord::parse(text, size)
+            .or_else(|| coord::parse_numbers(text, size))
+        else {
+            self.msg += &formatc!(
+                "{'r}Invalid coordinate `{text}`. Expected {} to {} or \
+                1,1 to {},{}.{'_}",
+                coord::format((0, size.y - 1).into(), size),
+                coord::format((size.x - 1, 0).into(), size),
+                size.x,
+                size.y,
+            );
+            return;
+        };
+
+        self.board.set_selected(pos);
+        self.gui_state.focus(pos);
+    }
+
+    fn command(&mut self, text: String) {
+        if text.trim().is_empty() {
+            return;
+        }
+        let res = Command::parse(&text)
+            .map_err(|e| formatc!("{'r}{e}{'_}"))
+            .and_then(|c| {
+                self.run_command(c).map_err(|e| formatc!("{'r}{e}{'_}"))
+            });
+        if let Err(e) = res {
+            self.msg += &e;
+        }
+        if self.history.last() != Some(&text) {
+            self.history.push(text);
+        }
+    }
+
+    fn run_command(&mut self, cmd: Command) -> Result<()> {
+        match cmd {
+            Command::Save(path) => {
+                Game::from_board(&self.board).save(&path, &[])?;
+                self.msg += &format!("Game saved to {}", path.display());
+            }
+            Command::Load(path) => self.load_game(&path)?,
+            Command::Size(size) => {
+                let mut game = NewGame::from_board(&self.board);
+                game.set_size(size);
+                self.set_board(game.board()?);
+            }
+            Command::Win(win_len) => {
+                self.new_game(self.board.start().clone(), win_len)?
+            }
+            Command::Undo(cnt) => {
+                if !self.can_undo() {
+                    return Ok(());
+                }
+                let cnt = cnt.min(self.board.moves().len());
+                for _ in 0..cnt {
+                    self.undo_move();
+                }
+                self.show_puzzle_task();
+                self.board_changed();
+            }
+            Command::Rules => {
+                self.msg += &format!(
+                    "Freestyle: line of at least {} stones wins.",
+                    self.board.win_len()
+                );
+            }
+            Command::New(settings) => {
+                let mut game = NewGame::from_board(&self.board);
+                if let Err(e) = game.parse(&settings) {
+                    self.msg += &formatc!("{'r}{e}{'_}");
+                    return Ok(());
+                }
+                self.set_board(game.board()?);
+            }
+            Command::Ai(suit, kind) => {
+                let ai = kind
+                    .map(|k| {
+                        Ai::new(
+                            k,
+                            self.playouts,
+                            self.move_time,
+                            Ai::player_seed(self.seed, suit),
+                            Book::load(self.book.as_deref())?,
+                        )
+                    })
+                    .transpose()?;
+                let player = match suit {
+                    Suit::Circle => &mut self.o_ai,
+                    _ => &mut self.x_ai,
+                };
+                *player = ai;
+                self.last_move = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the saved game and replays its moves.
+    fn load_game(&mut self, path: &Path) -> Result<()> {
+        let game = Game::load(path)?;
+        let mut board = game.board()?;
+        for m in game.moves() {
+            board.play_at(*m)?;
+        }
+        if let Some(m) = game.moves().last() {
+            board.set_selected(*m);
+        }
+        let ended = board.check_win() != Some(Suit::None);
+        self.set_board(board);
+        if ended {
+            self.board.inspect_mode();
+        }
+        self.msg += &format!("Game loaded from {}", path.display());
+        Ok(())
+    }
+
+    fn ask_new_game(&mut self, step: usize, game: NewGame) {
+        self.prompt = Some((
+            PromptKind::NewGame(step, game),
+            Prompt::new(game.question(step)),
+        ));
+    }
+
+    /// Sets the answer of the new game dialog and asks the next question.
+    /// The game starts after the last answer.
+    fn new_game_step(&mut self, step: usize, mut game: NewGame, answer: &str) {
+        if let Err(e) = game.answer(step, answer) {
+            self.msg += &formatc!("{'r}{e}{'_}");
+            return;
+        }
+        if step + 1 < NewGame::STEPS {
+            self.ask_new_game(step + 1, game);
+            return;
+        }
+        match game.board() {
+            Ok(board) => self.set_board(board),
+            Err(e) => self.msg += &formatc!("{'r}{e}{'_}"),
+        }
+    }
+
+    /// Starts new game from the position. Puzzles and tutorial are left.
+    fn new_game(&mut self, start: Position, win_len: usize) -> Result<()> {
+        let board = Board::new(start, win_len)?;
+        self.set_board(board);
+        Ok(())
+    }
+
+    /// Starts new game on the board. The match starts again because the
+    /// settings of the game may have changed.
+    fn set_board(&mut self, board: Board) {
+        self.board = board;
+        self.game_over = false;
+        if let Some(s) = &mut self.score {
+            s.restart(self.board.on_turn());
+        }
+        self.puzzles = None;
+        self.tutorial = None;
+        self.persistant_msg.clear();
+        if let Some(c) = &mut self.clock {
+            c.reset(self.board.on_turn());
+        }
+        self.gui_state.center();
+        self.out += formatc!("{'e _e_}");
+        self.board_changed();
+    }
+
+    fn zoom(&mut self, layout: Layout) {
+        self.gui_state.set_layout(layout);
+        self.out += formatc!("{'e _e_}");
+        self.msg += &format!("Cells of {} characters", layout.name());
+    }
+
+    fn toggle_labels(&mut self) {
+        let show = !self.gui_state.labels();
+        self.gui_state.set_labels(show);
+        self.out += formatc!("{'e _e_}");
+        if show {
+            self.msg += "Coordinates shown";
+        } else {
+            self.msg += "Coordinates hidden";
+        }
+    }
+
+    fn toggle_move_numbers(&mut self) {
+        let show = !self.gui_state.move_numbers();
+        self.gui_state.set_move_numbers(show);
+        if show {
+            self.msg += "Move numbers shown";
+        } else {
+            self.msg += "Move numbers hidden";
+        }
     }
 
     fn toggle_color(&mut self) {
@@ -270,11 +951,29 @@ impl Mainloop {
         }
     }
 
+    fn toggle_eval(&mut self) {
+        if let Some(mut a) = self.analyzer.take() {
+            a.stop();
+        } else {
+            let mut a = Analyzer::default();
+            a.start(&self.board);
+            self.analyzer = Some(a);
+        }
+    }
+
+    fn toggle_threats(&mut self) {
+        let show = !self.gui_state.threats();
+        self.gui_state.set_threats(show);
+        if show {
+            self.msg += "Threats shown";
+        } else {
+            self.msg += "Threats hidden";
+        }
+    }
+
     fn show_help(&mut self) {
         self.persistant_msg.clear();
-        self.persistant_msg +=
-            "[Arrows/wasd]move [Enter/Space/0]play [q]quit \
-            [r]restart [u]undo [h]help";
+        self.persistant_msg += &self.keymap.short_help();
     }
 }
 
@@ -282,3 +981,58 @@ fn terminal_size() -> Result<Vec2> {
     let size = raw::term_size()?;
     Ok((size.char_width, size.char_height).into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_after_win() {
+        let board = Board::new(Position::empty((5, 5)), 3).unwrap();
+        let mut ml = Mainloop::new(board, &Args::default()).unwrap();
+        ml.score = Some(MatchScore::new(3, Suit::Cross));
+        for pos in [(0, 0), (0, 1), (1, 0), (1, 1), (2, 0)] {
+            ml.board.set_selected(pos.into());
+            ml.play();
+        }
+        assert_eq!(ml.score.as_ref().unwrap().played(), 1);
+
+        ml.undo();
+        ml.command("undo 2".to_owned());
+        assert_eq!(ml.board.moves().len(), 5);
+        assert_eq!(ml.board.on_turn(), Suit::None);
+
+        ml.board.set_selected((2, 1).into());
+        ml.play();
+        assert_eq!(ml.score.as_ref().unwrap().played(), 1);
+
+        ml.reset();
+        ml.board.set_selected((0, 0).into());
+        ml.play();
+        ml.undo();
+        assert!(ml.board.moves().is_empty());
+    }
+
+    #[test]
+    fn test_size_keeps_settings() {
+        let mut start = Position::empty((9, 9));
+        start.set_on_turn(Suit::Circle);
+        let board = Board::new(start, 4).unwrap();
+        let mut ml = Mainloop::new(board, &Args::default()).unwrap();
+        ml.score = Some(MatchScore::new(3, Suit::Circle));
+        ml.score.as_mut().unwrap().record(Suit::Cross);
+        ml.play();
+
+        ml.command("size 11x7".to_owned());
+        assert_eq!(ml.board.size(), Vec2::new(11, 7));
+        assert_eq!(ml.board.win_len(), 4);
+        assert_eq!(ml.board.on_turn(), Suit::Circle);
+        assert!(ml.board.moves().is_empty());
+        let score = ml.score.as_ref().unwrap();
+        assert_eq!(score.played(), 0);
+        assert_eq!(score.starts(), Suit::Circle);
+
+        ml.command("size 100000x100000".to_owned());
+        assert_eq!(ml.board.size(), Vec2::new(11, 7));
+    }
+}
@@ -0,0 +1,129 @@
+use termal::formatc;
+
+use crate::suit::Suit;
+
+pub struct MatchScore {
+    games: usize,
+    cross: usize,
+    circle: usize,
+    draws: usize,
+    first: Suit,
+}
+
+impl MatchScore {
+    pub fn new(games: usize, first: Suit) -> Self {
+        Self {
+            games,
+            cross: 0,
+            circle: 0,
+            draws: 0,
+            first,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.games, self.first);
+    }
+
+    /// Starts new match with the same number of games where `first` starts.
+    pub fn restart(&mut self, first: Suit) {
+        *self = Self::new(self.games, first);
+    }
+
+    pub fn played(&self) -> usize {
+        self.cross + self.circle + self.draws
+    }
+
+    pub fn record(&mut self, winner: Suit) {
+        match winner {
+            Suit::None => self.draws += 1,
+            Suit::Cross => self.cross += 1,
+            Suit::Circle => self.circle += 1,
+        }
+    }
+
+    /// Gets the winner of the match if it is decided. [`Suit::None`] means
+    /// that the match ended with draw.
+    pub fn winner(&self) -> Option<Suit> {
+        let left = self.games - self.played();
+        if self.cross > self.circle + left {
+            Some(Suit::Cross)
+        } else if self.circle > self.cross + left {
+            Some(Suit::Circle)
+        } else {
+            (left == 0).then_some(Suit::None)
+        }
+    }
+
+    /// Gets who starts the next game. Players alternate in starting.
+    pub fn starts(&self) -> Suit {
+        if self.played().is_multiple_of(2) {
+            self.first
+        } else {
+            self.first.oposite()
+        }
+    }
+
+    pub fn format(&self) -> String {
+        let game = if self.winner().is_some() {
+            self.played()
+        } else {
+            self.played() + 1
+        };
+        let mut res = formatc!(
+            "Game {game}/{} {'b}X {'_}{}:{} {'r}O{'_}",
+            self.games,
+            self.cross,
+            self.circle
+        );
+        if self.draws != 0 {
+            res += &format!(" ({} draws)", self.draws);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_winner() {
+        let mut score = MatchScore::new(3, Suit::Cross);
+        assert_eq!(score.winner(), None);
+        score.record(Suit::Cross);
+        assert_eq!(score.winner(), None);
+        score.record(Suit::Cross);
+        assert_eq!(score.winner(), Some(Suit::Cross));
+
+        let mut score = MatchScore::new(3, Suit::Cross);
+        score.record(Suit::Circle);
+        score.record(Suit::None);
+        assert_eq!(score.winner(), None);
+        score.record(Suit::None);
+        assert_eq!(score.winner(), Some(Suit::Circle));
+    }
+
+    #[test]
+    fn test_winner_draw() {
+        let mut score = MatchScore::new(2, Suit::Cross);
+        score.record(Suit::Cross);
+        assert_eq!(score.winner(), None);
+        score.record(Suit::Circle);
+        assert_eq!(score.winner(), Some(Suit::None));
+
+        let mut score = MatchScore::new(1, Suit::Cross);
+        score.record(Suit::None);
+        assert_eq!(score.winner(), Some(Suit::None));
+    }
+
+    #[test]
+    fn test_starts() {
+        let mut score = MatchScore::new(4, Suit::Circle);
+        assert_eq!(score.starts(), Suit::Circle);
+        score.record(Suit::Cross);
+        assert_eq!(score.starts(), Suit::Cross);
+        score.record(Suit::None);
+        assert_eq!(score.starts(), Suit::Circle);
+    }
+}
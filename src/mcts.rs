@@ -0,0 +1,182 @@
+use std::time::{Duration, Instant};
+
+use crate::{board::Board, rng::Rng, suit::Suit, vec2::Vec2};
+
+// Exploration constant of UCT.
+const EXPLORATION: f64 = 1.4;
+
+/// Monte Carlo tree search. It uses only the legal moves and the win
+/// detection of [`Board`], so it plays any rules that the board supports
+/// without any heuristics.
+pub struct Mcts {
+    rng: Rng,
+    playouts: usize,
+}
+
+struct Node {
+    /// Move that leads to this node.
+    mov: Vec2,
+    /// Player that played `mov`.
+    suit: Suit,
+    children: Vec<usize>,
+    untried: Vec<Vec2>,
+    visits: u32,
+    /// Score of `suit`. Win is 1 and draw is 0.5.
+    score: f64,
+    /// Result of the game if it ends with `mov`.
+    result: Option<Suit>,
+}
+
+impl Mcts {
+    /// Creates search with `playouts` random games per move. The same seed
+    /// gives the same moves.
+    pub fn new(playouts: usize, rng: Rng) -> Self {
+        Self { rng, playouts }
+    }
+
+    /// Finds the best move for the player on turn. The search stops early if
+    /// `time` runs out.
+    pub fn best_move(
+        &mut self,
+        board: &Board,
+        time: Duration,
+    ) -> Option<Vec2> {
+        let deadline = Instant::now() + time;
+        let on_turn = board.on_turn();
+        if on_turn.is_none() {
+            return None;
+        }
+
+        let moves: Vec<_> = board.legal_moves().collect();
+        if moves.len() <= 1 {
+            return moves.first().copied();
+        }
+
+        let mut nodes = vec![Node {
+            mov: board.selected(),
+            suit: on_turn.oposite(),
+            children: vec![],
+            untried: moves,
+            visits: 0,
+            score: 0.,
+            result: None,
+        }];
+
+        for i in 0..self.playouts {
+            // Checking time is slower than playout on small boards.
+            if i % 64 == 0 && Instant::now() > deadline {
+                break;
+            }
+            self.playout(board, &mut nodes);
+        }
+
+        nodes[0]
+            .children
+            .iter()
+            .max_by_key(|c| nodes[**c].visits)
+            .map(|c| nodes[*c].mov)
+    }
+
+    fn playout(&mut self, board: &Board, nodes: &mut Vec<Node>) {
+        let mut board = board.clone();
+        let mut path = vec![0];
+
+        // Selection
+        let mut cur = 0;
+        while nodes[cur].untried.is_empty() && nodes[cur].result.is_none() {
+            cur = select(nodes, cur);
+            _ = board.play_at(nodes[cur].mov);
+            path.push(cur);
+        }
+
+        // Expansion
+        if nodes[cur].result.is_none() {
+            let untried = &mut nodes[cur].untried;
+            let mov = untried.swap_remove(self.rng.below(untried.len()));
+            let suit = board.on_turn();
+            _ = board.play_at(mov);
+            let result = game_result(&board, mov);
+            let child = Node {
+                mov,
+                suit,
+                children: vec![],
+                untried: if result.is_none() {
+                    board.legal_moves().collect()
+                } else {
+                    vec![]
+                },
+                visits: 0,
+                score: 0.,
+                result,
+            };
+            nodes.push(child);
+            let idx = nodes.len() - 1;
+            nodes[cur].children.push(idx);
+            cur = idx;
+            path.push(cur);
+        }
+
+        // Simulation
+        let winner = match nodes[cur].result {
+            Some(r) => r,
+            None => self.simulate(&mut board),
+        };
+
+        // Backpropagation
+        for n in path {
+            let node = &mut nodes[n];
+            node.visits += 1;
+            if winner == node.suit {
+                node.score += 1.;
+            } else if winner.is_none() {
+                node.score += 0.5;
+            }
+        }
+    }
+
+    /// Plays random moves until the game ends. Returns the winner.
+    fn simulate(&mut self, board: &mut Board) -> Suit {
+        loop {
+            let cnt = board.legal_moves().count();
+            if cnt == 0 {
+                return Suit::None;
+            }
+            let Some(mov) = board.legal_moves().nth(self.rng.below(cnt))
+            else {
+                return Suit::None;
+            };
+            let suit = board.on_turn();
+            _ = board.play_at(mov);
+            if board.wins_at(mov) {
+                return suit;
+            }
+        }
+    }
+}
+
+/// Selects child of `node` with the best upper confidence bound.
+fn select(nodes: &[Node], node: usize) -> usize {
+    let ln = (nodes[node].visits.max(1) as f64).ln();
+    let ucb = |c: usize| {
+        let n = &nodes[c];
+        let visits = n.visits as f64;
+        n.score / visits + EXPLORATION * (ln / visits).sqrt()
+    };
+    nodes[node]
+        .children
+        .iter()
+        .copied()
+        .max_by(|a, b| ucb(*a).total_cmp(&ucb(*b)))
+        .unwrap_or(node)
+}
+
+/// Gets the result of the game after `mov`. [`None`] if the game continues.
+fn game_result(board: &Board, mov: Vec2) -> Option<Suit> {
+    if board.wins_at(mov) {
+        Some(board[mov])
+    } else if board.legal_moves().next().is_none() {
+        Some(Suit::None)
+    } else {
+        None
+    }
+}
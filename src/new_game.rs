@@ -0,0 +1,175 @@
+use crate::{board::Board, err, position::Position, suit::Suit, vec2::Vec2};
+
+/// Maximum width and height of the board.
+pub const MAX_SIZE: usize = 1000;
+
+/// Settings of new game chosen in game with the new game dialog or with the
+/// command `new`. Values that are not given are the same as in the current
+/// game.
+#[derive(Clone, Copy)]
+pub struct NewGame {
+    size: Vec2,
+    win_len: usize,
+    starts: Suit,
+}
+
+impl NewGame {
+    /// Number of questions in the new game dialog.
+    pub const STEPS: usize = 4;
+
+    /// Gets the settings of the game on the board.
+    pub fn from_board(board: &Board) -> Self {
+        Self {
+            size: board.size(),
+            win_len: board.win_len(),
+            starts: board.start().on_turn(),
+        }
+    }
+
+    /// Gets the question of the dialog step with the current value.
+    pub fn question(&self, step: usize) -> String {
+        match step {
+            0 => format!("Board size [{}x{}]: ", self.size.x, self.size.y),
+            1 => format!("Win length [{}]: ", self.win_len),
+            2 => "Rules [freestyle]: ".to_owned(),
+            _ => format!("Who starts (x/o) [{}]: ", suit_name(self.starts)),
+        }
+    }
+
+    /// Sets the answer to the dialog step. Empty answer keeps the value.
+    pub fn answer(&mut self, step: usize, s: &str) -> Result<(), String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(());
+        }
+        match step {
+            0 => self.size = parse_size(s)?,
+            1 => self.win_len = parse_win_len(s)?,
+            2 => parse_rules(s)?,
+            _ => self.starts = parse_suit(s)?,
+        }
+        Ok(())
+    }
+
+    pub fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+    }
+
+    /// Sets the values given in any order, e.g. `19x19 6 o`.
+    pub fn parse(&mut self, s: &str) -> Result<(), String> {
+        for word in s.split_whitespace() {
+            if word.bytes().all(|b| b.is_ascii_digit()) {
+                self.win_len = parse_win_len(word)?;
+            } else if let Ok(suit) = parse_suit(word) {
+                self.starts = suit;
+            } else if word.starts_with(|c: char| c.is_ascii_digit()) {
+                self.size = parse_size(word)?;
+            } else {
+                parse_rules(word)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates empty board with the settings. Fails if the win length
+    /// doesn't fit the board.
+    pub fn board(&self) -> err::Result<Board> {
+        let mut start = Position::empty(self.size);
+        start.set_on_turn(self.starts);
+        Board::new(start, self.win_len)
+    }
+}
+
+/// Parses board size such as `15x15` or `15`.
+pub fn parse_size(s: &str) -> Result<Vec2, String> {
+    let (w, h) = s.split_once('x').unwrap_or((s, s));
+    match (w.trim().parse(), h.trim().parse()) {
+        (Ok(w), Ok(h)) if w > MAX_SIZE || h > MAX_SIZE => Err(format!(
+            "Invalid size `{s}`. The size must be at most \
+            {MAX_SIZE}x{MAX_SIZE}."
+        )),
+        (Ok(w), Ok(h)) if w != 0 && h != 0 => Ok((w, h).into()),
+        _ => Err(format!("Invalid size `{s}`. Expected for example `15x15`.")),
+    }
+}
+
+pub fn parse_win_len(s: &str) -> Result<usize, String> {
+    match s.parse() {
+        Ok(w) if w != 0 => Ok(w),
+        _ => Err(format!("Invalid win length `{s}`.")),
+    }
+}
+
+/// Checks the name of rules. Only freestyle (line of at least the win
+/// length wins) is supported.
+pub fn parse_rules(s: &str) -> Result<(), String> {
+    if s == "freestyle" {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown rules `{s}`. Only `freestyle` is supported."
+        ))
+    }
+}
+
+pub fn parse_suit(s: &str) -> Result<Suit, String> {
+    match s {
+        "x" | "X" => Ok(Suit::Cross),
+        "o" | "O" => Ok(Suit::Circle),
+        _ => Err(format!("Invalid player `{s}`. Expected `x` or `o`.")),
+    }
+}
+
+fn suit_name(suit: Suit) -> &'static str {
+    match suit {
+        Suit::Circle => "o",
+        _ => "x",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("15x13"), Ok(Vec2::new(15, 13)));
+        assert_eq!(parse_size("9"), Ok(Vec2::new(9, 9)));
+        assert!(parse_size("0x5").is_err());
+        assert!(parse_size("5x").is_err());
+        assert!(parse_size("ax5").is_err());
+        assert_eq!(parse_size("1000x1"), Ok(Vec2::new(1000, 1)));
+        assert!(parse_size("1001x5").is_err());
+        assert!(parse_size("100000x100000").is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let board = Board::new(Position::empty((15, 15)), 5).unwrap();
+        let mut new = NewGame::from_board(&board);
+        new.parse("o 19x17 freestyle 6").unwrap();
+        let board = new.board().unwrap();
+        assert_eq!(board.size(), Vec2::new(19, 17));
+        assert_eq!(board.win_len(), 6);
+        assert_eq!(board.on_turn(), Suit::Circle);
+
+        assert!(new.parse("renju").is_err());
+        assert!(new.parse("0").is_err());
+        new.parse("3 2x2").unwrap();
+        assert!(new.board().is_err());
+    }
+
+    #[test]
+    fn test_answer() {
+        let board = Board::new(Position::empty((15, 15)), 5).unwrap();
+        let mut new = NewGame::from_board(&board);
+        new.answer(0, " ").unwrap();
+        new.answer(1, "4").unwrap();
+        assert!(new.answer(2, "renju").is_err());
+        new.answer(3, "O").unwrap();
+        let board = new.board().unwrap();
+        assert_eq!(board.size(), Vec2::new(15, 15));
+        assert_eq!(board.win_len(), 4);
+        assert_eq!(board.on_turn(), Suit::Circle);
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    board::Board,
+    err::{Error, Result},
+    suit::Suit,
+    vec2::Vec2,
+};
+
+/// Extra time given to the engine before it is considered to be stuck.
+const GRACE_TIME: Duration = Duration::from_secs(1);
+
+/// External engine that uses the Piskvork protocol (the protocol of the
+/// gomoku AI tournaments). The engine gets the whole board before each move,
+/// so it doesn't need to follow undo.
+pub struct Piskvork {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    /// Size of the board that the engine was started with.
+    size: Option<Vec2>,
+    name: String,
+}
+
+impl Piskvork {
+    /// Starts the engine executable at `path`.
+    pub fn new(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| Error::Engine(name.clone(), e.to_string()))?;
+
+        let (Some(stdin), Some(stdout)) =
+            (child.stdin.take(), child.stdout.take())
+        else {
+            _ = child.kill();
+            return Err(Error::Engine(name, "Failed to connect.".into()));
+        };
+
+        let (send, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if send.send(line.trim().to_owned()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+            size: None,
+            name,
+        })
+    }
+
+    /// Asks the engine for move of the player on turn. The engine has `time`
+    /// to think.
+    pub fn best_move(
+        &mut self,
+        board: &Board,
+        time: Duration,
+    ) -> Result<Vec2> {
+        let me = board.on_turn();
+        if board.win_len() != 5 {
+            return Err(self.err("The engine can play only five in a row."));
+        }
+
+        if self.size != Some(board.size()) {
+            self.start(board.size())?;
+        }
+
+        self.send(&format!("INFO timeout_turn {}", time.as_millis()))?;
+        self.send("BOARD")?;
+        for pos in Vec2::new(0, 0).to(board.size()) {
+            let who = match board[pos] {
+                Suit::None => continue,
+                s if s == me => 1,
+                _ => 2,
+            };
+            self.send(&format!("{},{},{who}", pos.x, pos.y))?;
+        }
+        self.send("DONE")?;
+
+        let res = self.recv(time + GRACE_TIME)?;
+        let mov = res
+            .split_once(',')
+            .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+            .map(Vec2::from)
+            .ok_or_else(|| self.err(&format!("Invalid move `{res}`.")))?;
+        if !board.contains(mov) || !board[mov].is_none() {
+            return Err(self.err(&format!("Illegal move `{res}`.")));
+        }
+        Ok(mov)
+    }
+
+    fn start(&mut self, size: Vec2) -> Result<()> {
+        if size.x == size.y {
+            self.send(&format!("START {}", size.x))?;
+        } else {
+            self.send(&format!("RECTSTART {},{}", size.x, size.y))?;
+        }
+        let res = self.recv(GRACE_TIME * 10)?;
+        if res != "OK" {
+            return Err(self.err(&format!("Failed to start: {res}")));
+        }
+        // Five or more in a row wins.
+        self.send("INFO rule 0")?;
+        self.send("INFO timeout_match 0")?;
+        self.size = Some(size);
+        Ok(())
+    }
+
+    fn send(&mut self, line: &str) -> Result<()> {
+        writeln!(self.stdin, "{line}")
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| self.err(&e.to_string()))
+    }
+
+    /// Receives response from the engine skipping the informational
+    /// messages.
+    fn recv(&mut self, timeout: Duration) -> Result<String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let line = match self.lines.recv_timeout(left) {
+                Ok(l) => l,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(self.err("Timed out."))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(self.err("The engine exited."))
+                }
+            };
+            let (cmd, msg) = line.split_once(' ').unwrap_or((&line, ""));
+            match cmd {
+                "MESSAGE" | "DEBUG" | "SUGGEST" => {}
+                "ERROR" | "UNKNOWN" => return Err(self.err(msg)),
+                _ => return Ok(line),
+            }
+        }
+    }
+
+    fn err(&self, msg: &str) -> Error {
+        Error::Engine(self.name.clone(), msg.to_owned())
+    }
+}
+
+impl Drop for Piskvork {
+    fn drop(&mut self) {
+        _ = self.send("END");
+        _ = self.child.kill();
+        _ = self.child.wait();
+    }
+}
@@ -0,0 +1,207 @@
+use std::{fmt::Display, fs, path::Path};
+
+use pareg::{ArgError, FromArg};
+
+use crate::{board::Board, suit::Suit, vec2::Vec2};
+
+/// Starting position of a game.
+///
+/// In text form, each row is on separate line (or separated with `/`). Empty
+/// cells are `.`, crosses are `x` and circles are `o`. Whitespace is ignored.
+/// The position may be prefixed with `x:` or `o:` to set who is on turn. By
+/// default, circle is on turn if there are more crosses than circles and
+/// cross otherwise.
+#[derive(Debug, Clone)]
+pub struct Position {
+    size: Vec2,
+    cells: Vec<Suit>,
+    on_turn: Suit,
+}
+
+impl Position {
+    pub fn empty(size: impl Into<Vec2>) -> Self {
+        let size = size.into();
+        Self {
+            size,
+            cells: vec![Suit::None; size.prod()],
+            on_turn: Suit::Cross,
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    pub fn cells(&self) -> &[Suit] {
+        &self.cells
+    }
+
+    pub fn on_turn(&self) -> Suit {
+        self.on_turn
+    }
+
+    pub fn set_on_turn(&mut self, suit: Suit) {
+        self.on_turn = suit;
+    }
+
+    /// Gets the current position on the board.
+    pub fn from_board(board: &Board) -> Self {
+        Self {
+            size: board.size(),
+            cells: Vec2::new(0, 0)
+                .to(board.size())
+                .map(|p| board[p])
+                .collect(),
+            on_turn: board.on_turn(),
+        }
+    }
+
+    /// Checks whether all the cells are empty.
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().all(|c| c.is_none())
+    }
+
+    /// Loads the position from file if `arg` is path to existing file.
+    /// Otherwise parses `arg` as position.
+    pub fn load(arg: &str) -> pareg::Result<Self> {
+        if !Path::new(arg).is_file() {
+            return Self::from_arg(arg);
+        }
+
+        let s =
+            fs::read_to_string(arg).map_err(|e| ArgError::FailedToParse {
+                typ: "position",
+                value: arg.to_owned().into(),
+                msg: Some(format!("Failed to read the file: {e}").into()),
+            })?;
+        Self::parse(&s).map_err(|msg| ArgError::FailedToParse {
+            typ: "position",
+            value: arg.to_owned().into(),
+            msg: Some(msg.into()),
+        })
+    }
+
+    pub fn parse(s: &str) -> Result<Self, &'static str> {
+        let s = s.trim_start();
+        let (on_turn, s) = match s.get(..2) {
+            Some("x:" | "X:") => (Some(Suit::Cross), &s[2..]),
+            Some("o:" | "O:") => (Some(Suit::Circle), &s[2..]),
+            _ => (None, s),
+        };
+
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+        for row in s.split(['\n', '/']) {
+            let len = cells.len();
+            for c in row.chars().filter(|c| !c.is_whitespace()) {
+                cells.push(match c {
+                    '.' => Suit::None,
+                    'x' | 'X' => Suit::Cross,
+                    'o' | 'O' => Suit::Circle,
+                    _ => {
+                        return Err("Invalid cell. Expected `.`, `x` or `o`.")
+                    }
+                });
+            }
+
+            let row_len = cells.len() - len;
+            if row_len == 0 {
+                continue;
+            }
+            if *width.get_or_insert(row_len) != row_len {
+                return Err("All rows must have the same length.");
+            }
+            height += 1;
+        }
+
+        let Some(width) = width else {
+            return Err("The position is empty.");
+        };
+
+        let crosses = cells.iter().filter(|c| **c == Suit::Cross).count();
+        let circles = cells.iter().filter(|c| **c == Suit::Circle).count();
+        let on_turn = on_turn.unwrap_or(if crosses > circles {
+            Suit::Circle
+        } else {
+            Suit::Cross
+        });
+
+        Ok(Self {
+            size: (width, height).into(),
+            cells,
+            on_turn,
+        })
+    }
+}
+
+impl<'a> FromArg<'a> for Position {
+    fn from_arg(arg: &'a str) -> pareg::Result<Self> {
+        Self::parse(arg).map_err(|msg| ArgError::FailedToParse {
+            typ: "position",
+            value: arg.to_owned().into(),
+            msg: Some(msg.into()),
+        })
+    }
+}
+
+impl Display for Position {
+    /// Writes the position in the text form with rows separated by `/`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.on_turn {
+            Suit::Circle => write!(f, "o:")?,
+            _ => write!(f, "x:")?,
+        }
+        for (i, row) in self.cells.chunks(self.size.x).enumerate() {
+            if i != 0 {
+                write!(f, "/")?;
+            }
+            for c in row {
+                let c = match c {
+                    Suit::None => '.',
+                    Suit::Cross => 'x',
+                    Suit::Circle => 'o',
+                };
+                write!(f, "{c}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pos = Position::parse("..x/.o./x..").unwrap();
+        assert_eq!(pos.size(), Vec2::new(3, 3));
+        assert_eq!(pos.on_turn(), Suit::Circle);
+        assert_eq!(pos.cells()[2], Suit::Cross);
+        assert_eq!(pos.cells()[4], Suit::Circle);
+
+        let pos = Position::parse("o: . X \n . . \n").unwrap();
+        assert_eq!(pos.size(), Vec2::new(2, 2));
+        assert_eq!(pos.on_turn(), Suit::Circle);
+        assert_eq!(pos.cells()[1], Suit::Cross);
+
+        assert_eq!(Position::parse("x./o.").unwrap().on_turn(), Suit::Cross);
+        assert_eq!(Position::parse("x:x..").unwrap().on_turn(), Suit::Cross);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Position::parse("").is_err());
+        assert!(Position::parse("x:").is_err());
+        assert!(Position::parse("../...").is_err());
+        assert!(Position::parse("..a").is_err());
+    }
+
+    #[test]
+    fn test_parse_display() {
+        for s in ["x:..x/.o./x..", "o:x.../....", "x:."] {
+            assert_eq!(Position::parse(s).unwrap().to_string(), s);
+        }
+    }
+}
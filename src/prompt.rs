@@ -0,0 +1,137 @@
+use termal::raw::events::{Key, KeyCode, Modifiers};
+
+/// Line of text typed by the user in the message line.
+pub struct Prompt {
+    label: String,
+    text: String,
+    /// Previously submitted texts from the oldest.
+    history: Vec<String>,
+    /// Index of the shown history entry. It is `history.len()` when the
+    /// user edits new text.
+    history_pos: usize,
+    /// The new text while the history is browsed.
+    edited: String,
+    /// Words that may be completed with tab.
+    completions: &'static [&'static str],
+    /// Possible completions shown after ambiguous tab.
+    hint: String,
+}
+
+/// Result of key press in prompt.
+pub enum Input {
+    /// The text is still edited.
+    Edit,
+    /// The user confirmed the text.
+    Submit(String),
+    /// The user canceled the prompt.
+    Cancel,
+}
+
+impl Prompt {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            text: String::new(),
+            history: vec![],
+            history_pos: 0,
+            edited: String::new(),
+            completions: &[],
+            hint: String::new(),
+        }
+    }
+
+    /// Sets the history that may be browsed with up and down arrows.
+    pub fn set_history(&mut self, history: Vec<String>) {
+        self.history_pos = history.len();
+        self.history = history;
+    }
+
+    /// Sets the words that complete the first word of the text on tab.
+    pub fn set_completions(&mut self, completions: &'static [&'static str]) {
+        self.completions = completions;
+    }
+
+    pub fn key(&mut self, key: &Key) -> Input {
+        self.hint.clear();
+        match key.code {
+            KeyCode::Enter => return Input::Submit(self.text.clone()),
+            KeyCode::Esc => return Input::Cancel,
+            KeyCode::Char('c') if key.modifiers == Modifiers::CONTROL => {
+                return Input::Cancel
+            }
+            KeyCode::Backspace => {
+                if self.text.pop().is_none() {
+                    return Input::Cancel;
+                }
+            }
+            KeyCode::Up => self.browse(-1),
+            KeyCode::Down => self.browse(1),
+            KeyCode::Tab => self.complete(),
+            _ => {
+                if let Some(c) = key.key_char.filter(|c| !c.is_control()) {
+                    self.text.push(c);
+                }
+            }
+        }
+        Input::Edit
+    }
+
+    /// Formats the prompt with the text and cursor.
+    pub fn format(&self) -> String {
+        if self.hint.is_empty() {
+            format!("{}{}_", self.label, self.text)
+        } else {
+            format!("{}{}_  \x1b[90m{}", self.label, self.text, self.hint)
+        }
+    }
+
+    fn browse(&mut self, dir: isize) {
+        let Some(pos) = self
+            .history_pos
+            .checked_add_signed(dir)
+            .filter(|p| *p <= self.history.len())
+        else {
+            return;
+        };
+
+        if self.history_pos == self.history.len() {
+            self.edited = self.text.clone();
+        }
+        self.history_pos = pos;
+        self.text = self.history.get(pos).unwrap_or(&self.edited).clone();
+    }
+
+    /// Completes the first word to the longest common prefix of the
+    /// matching words.
+    fn complete(&mut self) {
+        if self.text.contains(' ') {
+            return;
+        }
+
+        let matching: Vec<_> = self
+            .completions
+            .iter()
+            .filter(|c| c.starts_with(&self.text))
+            .collect();
+        match matching.as_slice() {
+            [] => {}
+            [word] => self.text = format!("{word} "),
+            [first, rest @ ..] => {
+                let len = rest.iter().fold(first.len(), |len, w| {
+                    first
+                        .bytes()
+                        .zip(w.bytes())
+                        .take(len)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                self.text = first[..len].to_owned();
+                self.hint = matching
+                    .iter()
+                    .map(|w| w.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+        }
+    }
+}
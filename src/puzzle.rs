@@ -0,0 +1,285 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use termal::printmcln;
+
+use crate::{
+    board::Board,
+    dirs,
+    err::{Error, Result},
+    game::Game,
+    position::Position,
+    suit::Suit,
+    tss::Tss,
+};
+
+const PUZZLES_FILE: &str = "puzzles.tsv";
+/// Time to look for forced win when building puzzles.
+const BUILD_TIME: Duration = Duration::from_millis(200);
+
+/// Position where the player on turn has forced win in the given number of
+/// moves.
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    start: Position,
+    win_len: usize,
+    moves: usize,
+}
+
+/// Progress of the solver after a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    Solved,
+    Failed,
+    Playing,
+}
+
+/// List of puzzles. Each line of the file has the tab separated fields: win
+/// length, number of moves to win and the position in the same format as
+/// `--position` with rows separated by `/`.
+#[derive(Debug, Clone, Default)]
+pub struct Puzzles {
+    list: Vec<Puzzle>,
+    cur: usize,
+}
+
+impl Puzzle {
+    pub fn board(&self) -> Result<Board> {
+        Board::new(self.start.clone(), self.win_len)
+    }
+
+    /// Gets the player that should win.
+    pub fn solver(&self) -> Suit {
+        self.start.on_turn()
+    }
+
+    /// Gets the task of the puzzle, e.g. `X to play and win in 3`.
+    pub fn task(&self) -> String {
+        let who = if self.solver() == Suit::Circle {
+            "O"
+        } else {
+            "X"
+        };
+        format!("{who} to play and win in {}", self.moves)
+    }
+
+    /// Checks the progress of the solver. The game should be finished or
+    /// the solver should be on turn. The puzzle fails only if the solver
+    /// doesn't win in the given number of moves, so any winning line is
+    /// accepted.
+    pub fn progress(&self, board: &Board) -> Progress {
+        let solver = self.solver();
+        let played = board.moves().len().div_ceil(2);
+        if let Some(&last) = board.moves().last() {
+            if board.wins_at(last) {
+                return if board[last] == solver && played <= self.moves {
+                    Progress::Solved
+                } else {
+                    Progress::Failed
+                };
+            }
+        }
+
+        if board.on_turn() != solver || played >= self.moves {
+            Progress::Failed
+        } else {
+            Progress::Playing
+        }
+    }
+}
+
+impl Puzzles {
+    /// Loads the puzzles from `path` or from the data directory.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(p) => p.to_owned(),
+            None => Self::file()?,
+        };
+        let s = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let list = s
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty() && !l.starts_with('#'))
+            .map(|(i, l)| parse_line(l).ok_or(Error::InvalidPuzzle(i + 1)))
+            .collect::<Result<_>>()?;
+        Ok(Self { list, cur: 0 })
+    }
+
+    /// Saves the puzzles to `path` or to the data directory.
+    pub fn save(&self, path: Option<&Path>) -> Result<PathBuf> {
+        let path = match path {
+            Some(p) => p.to_owned(),
+            None => Self::file()?,
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let s: String = self
+            .list
+            .iter()
+            .map(|p| format!("{}\t{}\t{}\n", p.win_len, p.moves, p.start))
+            .collect();
+        fs::write(&path, s)?;
+        Ok(path)
+    }
+
+    /// Adds puzzles found in the saved games in `dir`. From each game, the
+    /// first position where the player on turn has victory by continuous
+    /// fours in at least two moves is used. Positions that are already
+    /// present are skipped.
+    pub fn build(&mut self, dir: impl AsRef<Path>, color: bool) -> Result<()> {
+        let mut files: Vec<_> = fs::read_dir(dir)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<std::io::Result<_>>()?;
+        files.sort();
+
+        let mut known = HashSet::new();
+        for p in &self.list {
+            if let Ok(board) = p.board() {
+                known.insert(board.canonical_hash());
+            }
+        }
+
+        for file in files.iter().filter(|f| f.is_file()) {
+            match find_puzzle(file) {
+                Ok(Some((puzzle, hash))) => {
+                    if known.insert(hash) {
+                        self.list.push(puzzle);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => printmcln!(
+                    color,
+                    "{'y}warning: {'_}skipping {}: {e}",
+                    file.display()
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Gets the index of the current puzzle.
+    pub fn index(&self) -> usize {
+        self.cur
+    }
+
+    pub fn current(&self) -> &Puzzle {
+        &self.list[self.cur]
+    }
+
+    /// Moves to the next puzzle. After the last puzzle is the first one.
+    pub fn next(&mut self) -> &Puzzle {
+        self.cur = (self.cur + 1) % self.list.len();
+        self.current()
+    }
+
+    fn file() -> Result<PathBuf> {
+        Ok(dirs::data_dir().ok_or(Error::NoDataDir)?.join(PUZZLES_FILE))
+    }
+}
+
+/// Finds the first position with forced win in at least two moves in the
+/// game. Returns also the canonical hash of the position.
+fn find_puzzle(file: &Path) -> Result<Option<(Puzzle, u64)>> {
+    let game = Game::load(file)?;
+    let mut board = game.board()?;
+    for &mov in game.moves() {
+        // Win in one move is too easy, so look for a later position.
+        if let Some(line) =
+            Tss::vcf(&board, BUILD_TIME).filter(|l| l.len().div_ceil(2) >= 2)
+        {
+            let moves = line.len().div_ceil(2);
+            let puzzle = Puzzle {
+                start: Position::from_board(&board),
+                win_len: board.win_len(),
+                moves,
+            };
+            return Ok(Some((puzzle, board.canonical_hash())));
+        }
+        board.play_at(mov)?;
+        if board.wins_at(mov) {
+            break;
+        }
+    }
+    Ok(None)
+}
+
+fn parse_line(line: &str) -> Option<Puzzle> {
+    let mut fields = line.split('\t');
+    let win_len = fields.next()?.parse().ok().filter(|w| *w != 0)?;
+    let moves = fields.next()?.parse().ok().filter(|m| *m != 0)?;
+    let start = Position::parse(fields.next()?).ok()?;
+    fields.next().is_none().then_some(Puzzle {
+        start,
+        win_len,
+        moves,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle() -> Puzzle {
+        Puzzle {
+            start: Position::parse(
+                "x:......./..xxx../......./..oo.../.......",
+            )
+            .unwrap(),
+            win_len: 5,
+            moves: 2,
+        }
+    }
+
+    fn progress(moves: &[(usize, usize)]) -> Progress {
+        let puzzle = puzzle();
+        let mut board = puzzle.board().unwrap();
+        for m in moves {
+            board.play_at((*m).into()).unwrap();
+        }
+        puzzle.progress(&board)
+    }
+
+    #[test]
+    fn test_progress() {
+        assert_eq!(progress(&[(5, 1), (0, 0)]), Progress::Playing);
+        assert_eq!(progress(&[(5, 1), (1, 1), (6, 1)]), Progress::Solved);
+        // The other side also wins.
+        assert_eq!(progress(&[(1, 1), (5, 1), (0, 1)]), Progress::Solved);
+        assert_eq!(progress(&[(0, 0), (5, 1), (6, 4)]), Progress::Failed);
+    }
+
+    #[test]
+    fn test_progress_defender_wins() {
+        let puzzle = Puzzle {
+            moves: 3,
+            ..puzzle()
+        };
+        let mut board = puzzle.board().unwrap();
+        for m in [(0, 0), (1, 3), (6, 4), (4, 3), (6, 0), (5, 3)] {
+            board.play_at(m.into()).unwrap();
+        }
+        assert_eq!(puzzle.progress(&board), Progress::Failed);
+    }
+}
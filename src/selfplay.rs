@@ -0,0 +1,119 @@
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+use termal::{eprintmc, printmcln};
+
+use crate::{
+    ai::Ai,
+    args::Args,
+    board::Board,
+    book::Book,
+    err::{Error, Result},
+    game::Game,
+    suit::Suit,
+};
+
+/// Plays the game on `board` between the computer players without user
+/// interface. Returns the winner. The player whose computer fails loses and
+/// the error is returned with the winner.
+pub fn play_game(
+    board: &mut Board,
+    x: &mut Ai,
+    o: &mut Ai,
+) -> Result<(Suit, Option<Error>)> {
+    loop {
+        let suit = board.on_turn();
+        let ai = match suit {
+            Suit::Cross => &mut *x,
+            Suit::Circle => &mut *o,
+            Suit::None => return Ok((Suit::None, None)),
+        };
+        let mov = match ai.best_move(board) {
+            Ok(Some(mov)) => mov,
+            Ok(None) => return Ok((Suit::None, None)),
+            Err(e) => return Ok((suit.oposite(), Some(e))),
+        };
+        board.play_at(mov)?;
+        if board.wins_at(mov) {
+            return Ok((suit, None));
+        }
+        if board.legal_moves().next().is_none() {
+            return Ok((Suit::None, None));
+        }
+    }
+}
+
+/// Plays the given number of games between the computer players set for
+/// cross and circle and prints the statistics. The players alternate the
+/// colours.
+pub fn selfplay(args: &Args) -> Result<()> {
+    let color = args.color();
+    let book = Book::load(args.book())?;
+    let kinds =
+        [Suit::Cross, Suit::Circle].map(|s| args.ai(s).unwrap_or_default());
+    let mut ais = [
+        Ai::for_player(args, Suit::Cross, kinds[0].clone(), book.clone())?,
+        Ai::for_player(args, Suit::Circle, kinds[1].clone(), book)?,
+    ];
+
+    let games = args.games().unwrap_or(100);
+    if let Some(dir) = args.out_dir() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut wins = [0; 2];
+    let mut draws = 0;
+    for game in 0..games {
+        eprintmc!(color, "\r{'gr}Playing game {}/{games}...{'_}", game + 1);
+        _ = io::stderr().flush();
+
+        // The first player plays cross in the even games.
+        let swap = game % 2 == 1;
+        let [a, b] = &mut ais;
+        let (x, o) = if swap { (b, a) } else { (a, b) };
+        let mut board = Board::new(args.position(), args.win_len())?;
+        let (winner, err) = play_game(&mut board, x, o)?;
+        if let Some(dir) = args.out_dir() {
+            let (xi, oi) = if swap { (1, 0) } else { (0, 1) };
+            let comments = [
+                format!("X: {}. {}", xi + 1, kinds[xi].name()),
+                format!("O: {}. {}", oi + 1, kinds[oi].name()),
+            ];
+            Game::from_board(&board)
+                .save(dir.join(format!("{:04}.txt", game + 1)), &comments)?;
+        }
+
+        let winner = match winner {
+            Suit::None => {
+                draws += 1;
+                continue;
+            }
+            Suit::Cross => swap as usize,
+            Suit::Circle => !swap as usize,
+        };
+        wins[winner] += 1;
+        if let Some(e) = err {
+            eprintmc!(color, "\r\x1b[K{'y}warning: {'_}{e}\n");
+        }
+    }
+    eprintmc!(color, "\r\x1b[K");
+
+    printmcln!(color, "{'g}Games:{'_} {games}");
+    for (i, kind) in kinds.iter().enumerate() {
+        printmcln!(
+            color,
+            "  {'w}{}. {}{'_}: {} wins ({:.1} %)",
+            i + 1,
+            kind.name(),
+            wins[i],
+            wins[i] as f64 * 100. / games as f64
+        );
+    }
+    printmcln!(
+        color,
+        "  {'w}Draws{'_}: {draws} ({:.1} %)",
+        draws as f64 * 100. / games as f64
+    );
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use std::{fs, io::ErrorKind, path::PathBuf};
+
+use termal::printmcln;
+
+use crate::{
+    dirs,
+    err::{Error, Result},
+    suit::Suit,
+};
+
+const STATS_FILE: &str = "stats.tsv";
+const START_ELO: f64 = 1500.;
+const ELO_K: f64 = 32.;
+
+struct PlayerStats {
+    name: String,
+    wins: usize,
+    losses: usize,
+    draws: usize,
+    elo: f64,
+}
+
+/// Player statistics stored in the data directory. Each line of the file
+/// has the tab separated fields: name, wins, losses, draws and elo.
+#[derive(Default)]
+pub struct Stats {
+    players: Vec<PlayerStats>,
+}
+
+impl PlayerStats {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            elo: START_ELO,
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let res = Self {
+            name: fields.next()?.to_owned(),
+            wins: fields.next()?.parse().ok()?,
+            losses: fields.next()?.parse().ok()?,
+            draws: fields.next()?.parse().ok()?,
+            elo: fields.next()?.parse().ok()?,
+        };
+        fields.next().is_none().then_some(res)
+    }
+
+    fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+}
+
+impl Stats {
+    pub fn load() -> Result<Self> {
+        let s = match fs::read_to_string(Self::file()?) {
+            Ok(s) => s,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(Self::default())
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Self::parse(&s)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = Self::file()?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        Ok(fs::write(file, self.format())?)
+    }
+
+    fn parse(s: &str) -> Result<Self> {
+        let players = s
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| {
+                PlayerStats::parse(l).ok_or(Error::InvalidStats(i + 1))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { players })
+    }
+
+    /// Formats the stats as the content of the file.
+    fn format(&self) -> String {
+        let mut s = String::new();
+        for p in &self.players {
+            s += &format!(
+                "{}\t{}\t{}\t{}\t{:.1}\n",
+                p.name, p.wins, p.losses, p.draws, p.elo
+            );
+        }
+        s
+    }
+
+    /// Records result of game between the two players and updates their
+    /// elo.
+    pub fn record(&mut self, cross: &str, circle: &str, winner: Suit) {
+        let x_elo = self.player(cross).elo;
+        let o_elo = self.player(circle).elo;
+        let x_expected = 1. / (1. + 10f64.powf((o_elo - x_elo) / 400.));
+        let x_score = match winner {
+            Suit::Cross => 1.,
+            Suit::Circle => 0.,
+            Suit::None => 0.5,
+        };
+
+        let x = self.player(cross);
+        x.elo += ELO_K * (x_score - x_expected);
+        match winner {
+            Suit::Cross => x.wins += 1,
+            Suit::Circle => x.losses += 1,
+            Suit::None => x.draws += 1,
+        }
+
+        let o = self.player(circle);
+        o.elo += ELO_K * (x_expected - x_score);
+        match winner {
+            Suit::Cross => o.losses += 1,
+            Suit::Circle => o.wins += 1,
+            Suit::None => o.draws += 1,
+        }
+    }
+
+    pub fn print(&mut self, color: bool) {
+        if self.players.is_empty() {
+            printmcln!(
+                color,
+                "No games recorded. Name the players with {'y}--x-player{'_} \
+                and {'y}--o-player{'_} to track their statistics."
+            );
+            return;
+        }
+
+        self.players.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+        let width = self
+            .players
+            .iter()
+            .map(|p| p.name.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(4);
+
+        printmcln!(
+            color,
+            "{'g}{:<width$}  {:>5}  {:>5}  {:>6}  {:>5}  {:>6}{'_}",
+            "Name",
+            "Games",
+            "Wins",
+            "Losses",
+            "Draws",
+            "Elo"
+        );
+        for p in &self.players {
+            printmcln!(
+                color,
+                "{'y}{:<width$}{'_}  {:>5}  {:>5}  {:>6}  {:>5}  {'w}{:>6.0}{'_}",
+                p.name,
+                p.games(),
+                p.wins,
+                p.losses,
+                p.draws,
+                p.elo
+            );
+        }
+    }
+
+    fn player(&mut self, name: &str) -> &mut PlayerStats {
+        let idx = match self.players.iter().position(|p| p.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.players.push(PlayerStats::new(name.to_owned()));
+                self.players.len() - 1
+            }
+        };
+        &mut self.players[idx]
+    }
+
+    fn file() -> Result<PathBuf> {
+        Ok(dirs::data_dir().ok_or(Error::NoDataDir)?.join(STATS_FILE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elo(stats: &mut Stats, name: &str) -> f64 {
+        stats.player(name).elo
+    }
+
+    #[test]
+    fn test_record() {
+        let mut stats = Stats::default();
+        stats.record("a", "b", Suit::Cross);
+        assert_eq!(elo(&mut stats, "a"), START_ELO + ELO_K / 2.);
+        assert_eq!(elo(&mut stats, "b"), START_ELO - ELO_K / 2.);
+        let a = stats.player("a");
+        assert_eq!((a.wins, a.losses, a.draws), (1, 0, 0));
+        let b = stats.player("b");
+        assert_eq!((b.wins, b.losses, b.draws), (0, 1, 0));
+
+        // Win against weaker player gives less.
+        stats.record("c", "a", Suit::Circle);
+        let gain = elo(&mut stats, "a") - START_ELO - ELO_K / 2.;
+        assert!(gain > 0. && gain < ELO_K / 2.);
+        assert_eq!(elo(&mut stats, "c"), START_ELO - gain);
+    }
+
+    #[test]
+    fn test_record_draw() {
+        let mut stats = Stats::default();
+        stats.record("a", "b", Suit::None);
+        assert_eq!(elo(&mut stats, "a"), START_ELO);
+        assert_eq!(elo(&mut stats, "b"), START_ELO);
+        assert_eq!(stats.player("a").draws, 1);
+        assert_eq!(stats.player("b").draws, 1);
+
+        // Draw against stronger player gains.
+        stats.record("a", "c", Suit::Cross);
+        stats.record("b", "a", Suit::None);
+        assert!(elo(&mut stats, "b") > START_ELO);
+    }
+
+    #[test]
+    fn test_parse_format() {
+        let mut stats = Stats::default();
+        stats.record("a", "b b", Suit::Cross);
+        stats.record("b b", "c", Suit::None);
+        let s = stats.format();
+        let mut loaded = Stats::parse(&s).unwrap();
+        assert_eq!(loaded.format(), s);
+        assert_eq!(loaded.players.len(), 3);
+        assert_eq!(loaded.player("b b").games(), 2);
+        assert!(Stats::parse("\n").unwrap().players.is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for (s, line) in [
+            ("a\t1\t0\t0", 1),
+            ("a\t1\t0\t0\t1500\t3", 1),
+            ("a\t1\t0\t0\t1500\n\nb\tx\t0\t0\t1500", 3),
+            ("a\t1\t0\t0\telo", 1),
+        ] {
+            match Stats::parse(s) {
+                Err(Error::InvalidStats(l)) => assert_eq!(l, line, "{s}"),
+                _ => panic!("{s}"),
+            }
+        }
+    }
+}
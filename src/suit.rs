@@ -1,5 +1,6 @@
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
 pub enum Suit {
+    #[default]
     None,
     Cross,
     Circle,
@@ -0,0 +1,124 @@
+use pareg::{ArgError, FromArg};
+use termal::codes;
+
+use crate::suit::Suit;
+
+/// How the selected cell is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cursor {
+    /// Marks of the player on turn in the corners of the cell.
+    Marks,
+    /// Marks in the corners and brackets around the symbol in the cell.
+    Brackets,
+}
+
+/// Colors and symbols used to draw the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    cross: &'static str,
+    circle: &'static str,
+    grid: &'static str,
+    cursor: Cursor,
+    cross_symbol: char,
+    circle_symbol: char,
+}
+
+impl Theme {
+    pub const DEFAULT: Theme = Theme {
+        cross: codes::BLUE_FG,
+        circle: codes::RED_FG,
+        grid: codes::GRAY_FG,
+        cursor: Cursor::Marks,
+        cross_symbol: 'X',
+        circle_symbol: 'O',
+    };
+
+    /// Blue and orange that are distinguishable with the common types of
+    /// color blindness.
+    pub const COLORBLIND: Theme = Theme {
+        cross: codes::BLUE_FG,
+        // orange
+        circle: "\x1b[38;5;208m",
+        ..Self::DEFAULT
+    };
+
+    pub const HIGH_CONTRAST: Theme = Theme {
+        cross: codes::CYAN_FG,
+        circle: codes::YELLOW_FG,
+        grid: codes::GRAY_BRIGHT_FG,
+        cursor: Cursor::Brackets,
+        ..Self::DEFAULT
+    };
+
+    pub const MONO: Theme = Theme {
+        cross: codes::WHITE_FG,
+        circle: codes::WHITE_FG,
+        grid: codes::GRAY_FG,
+        cursor: Cursor::Brackets,
+        ..Self::DEFAULT
+    };
+
+    pub fn color(&self, suit: Suit) -> &'static str {
+        match suit {
+            Suit::Cross => self.cross,
+            Suit::Circle => self.circle,
+            Suit::None => codes::WHITE_FG,
+        }
+    }
+
+    pub fn grid(&self) -> &'static str {
+        self.grid
+    }
+
+    pub fn cursor(&self) -> Cursor {
+        self.cursor
+    }
+
+    /// Gets the symbol drawn in cell with the given suit.
+    pub fn symbol(&self, suit: Suit) -> char {
+        match suit {
+            Suit::Cross => self.cross_symbol,
+            Suit::Circle => self.circle_symbol,
+            Suit::None => ' ',
+        }
+    }
+
+    /// Gets the mark of the player on turn used to draw the cursor.
+    pub fn mark(&self, suit: Suit) -> char {
+        match suit {
+            Suit::None => '#',
+            s => self.symbol(s).to_ascii_lowercase(),
+        }
+    }
+
+    pub fn set_symbols(&mut self, cross: char, circle: char) {
+        self.cross_symbol = cross;
+        self.circle_symbol = circle;
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl<'a> FromArg<'a> for Theme {
+    fn from_arg(arg: &'a str) -> pareg::Result<Self> {
+        match arg {
+            "default" => Ok(Self::DEFAULT),
+            "colorblind" | "colourblind" => Ok(Self::COLORBLIND),
+            "high-contrast" => Ok(Self::HIGH_CONTRAST),
+            "mono" => Ok(Self::MONO),
+            _ => Err(ArgError::FailedToParse {
+                typ: "theme",
+                value: arg.to_owned().into(),
+                msg: Some(
+                    "Expected `default`, `colorblind`, `high-contrast` or \
+                    `mono`."
+                        .into(),
+                ),
+            }),
+        }
+    }
+}
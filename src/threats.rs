@@ -0,0 +1,191 @@
+use crate::{board::Board, suit::Suit, vec2::Vec2};
+
+const DIRS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (-1, 1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Threat {
+    #[default]
+    None,
+    /// Playing here creates two cells in line where the player wins, e.g.
+    /// open line of `win_len - 1` (open four).
+    OpenFour,
+    /// Playing here wins.
+    Win,
+}
+
+/// Threats of both players on the empty cells of the board.
+pub struct Threats {
+    size: Vec2,
+    cells: Vec<(Threat, Threat)>,
+}
+
+impl Threats {
+    pub fn new(board: &Board) -> Self {
+        let size = board.size();
+        let mut cells = vec![(Threat::None, Threat::None); size.prod()];
+        if board.win_len() >= 3 {
+            for pos in Vec2::new(0, 0).to(size) {
+                if board[pos].is_none() {
+                    cells[pos.y * size.x + pos.x] = (
+                        threat_at(board, pos, Suit::Cross),
+                        threat_at(board, pos, Suit::Circle),
+                    );
+                }
+            }
+        }
+        Self { size, cells }
+    }
+
+    pub fn get(&self, pos: Vec2, suit: Suit) -> Threat {
+        let (cross, circle) = self.cells[pos.y * self.size.x + pos.x];
+        match suit {
+            Suit::Cross => cross,
+            Suit::Circle => circle,
+            Suit::None => Threat::None,
+        }
+    }
+
+    /// Gets the most important threat at the position. If both players have
+    /// the same threat, `prefer` is chosen.
+    pub fn strongest(&self, pos: Vec2, prefer: Suit) -> (Suit, Threat) {
+        let other = match prefer {
+            Suit::None => Suit::Cross,
+            s => s.oposite(),
+        };
+        let p = self.get(pos, prefer);
+        let o = self.get(pos, other);
+        if o > p {
+            (other, o)
+        } else {
+            (prefer, p)
+        }
+    }
+}
+
+/// Gets the threat that `suit` has at the empty cell `pos`.
+pub fn threat_at(board: &Board, pos: Vec2, suit: Suit) -> Threat {
+    DIRS.into_iter()
+        .map(|d| line_threat(board, pos, d.into(), suit))
+        .max()
+        .unwrap_or_default()
+}
+
+/// Gets the threat of `suit` at `pos` in the line with direction `dir`. All
+/// the windows of the win length that contain `pos` are checked, so lines
+/// with gaps such as `XX.XX` are also found.
+fn line_threat(
+    board: &Board,
+    pos: Vec2,
+    dir: Vec2<isize>,
+    suit: Suit,
+) -> Threat {
+    let win_len = board.win_len() as isize;
+    // Offset from `pos` of the cell where `suit` would win after playing at
+    // `pos` and whether there is another such cell.
+    let mut first_win = None;
+    let mut two_wins = false;
+    for start in 1 - win_len..=0 {
+        let first = pos.signed() + dir.cmul((start, start));
+        let last = first + dir.cmul((win_len - 1, win_len - 1));
+        if !board.contains_signed(first) || !board.contains_signed(last) {
+            continue;
+        }
+
+        let mut empty = None;
+        let mut blocked = false;
+        for i in start..start + win_len {
+            let p = (pos.signed() + dir.cmul((i, i))).unsigned();
+            let cell = board[p];
+            if i == 0 || cell == suit {
+                continue;
+            }
+            if !cell.is_none() || empty.is_some() {
+                blocked = true;
+                break;
+            }
+            empty = Some(i);
+        }
+
+        match empty {
+            _ if blocked => {}
+            None => return Threat::Win,
+            Some(e) => two_wins |= *first_win.get_or_insert(e) != e,
+        }
+    }
+
+    if two_wins && win_len >= 4 {
+        Threat::OpenFour
+    } else {
+        Threat::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::position::Position;
+
+    use super::*;
+
+    fn row(s: &str) -> Board {
+        Board::new(Position::parse(s).unwrap(), 5).unwrap()
+    }
+
+    fn threats(s: &str) -> Vec<Threat> {
+        let board = row(s);
+        (0..s.len())
+            .map(|x| {
+                let pos = Vec2::new(x, 0);
+                if board[pos].is_none() {
+                    threat_at(&board, pos, Suit::Cross)
+                } else {
+                    Threat::None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_win() {
+        use Threat::*;
+        assert_eq!(threats(".xxxx."), [Win, None, None, None, None, Win]);
+        assert_eq!(threats("xx.xx"), [None, None, Win, None, None]);
+        assert_eq!(threats("x.xxx"), [None, Win, None, None, None]);
+        assert_eq!(threats("oxxxx."), [None, None, None, None, None, Win]);
+        assert_eq!(threats("xx.xo"), [None; 5]);
+    }
+
+    #[test]
+    fn test_open_four() {
+        use Threat::*;
+        assert_eq!(
+            threats("..xxx.."),
+            [None, OpenFour, None, None, None, OpenFour, None]
+        );
+        assert_eq!(
+            threats("..xx.x.."),
+            [None, None, None, None, OpenFour, None, None, None]
+        );
+        assert_eq!(
+            threats(".x.xx."),
+            [None, None, OpenFour, None, None, None]
+        );
+        assert_eq!(threats("oxxx.."), [None; 6]);
+    }
+
+    #[test]
+    fn test_strongest() {
+        let board = row("oooo.xxx..");
+        let threats = Threats::new(&board);
+        let pos = Vec2::new(4, 0);
+        assert_eq!(threats.get(pos, Suit::Cross), Threat::None);
+        assert_eq!(
+            threats.strongest(pos, Suit::Cross),
+            (Suit::Circle, Threat::Win)
+        );
+        let pos = Vec2::new(8, 0);
+        assert_eq!(
+            threats.strongest(pos, Suit::Circle),
+            (Suit::Cross, Threat::OpenFour)
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::{fmt::Write, fs, path::Path};
+
+use termal::printmcln;
+
+use crate::{
+    ai::{Ai, AiKind},
+    args::Args,
+    board::Board,
+    book::Book,
+    err::Result,
+    game::Game,
+    selfplay::play_game,
+    suit::Suit,
+};
+
+const CROSSTABLE_FILE: &str = "crosstable.txt";
+
+/// Runs tournament between the engines given by the arguments. In round
+/// robin, each engine plays against each other engine. In gauntlet, the first
+/// engine plays against all the others. Each pairing plays the given number
+/// of games with alternating colors. All the games and the crosstable are
+/// saved to the output directory.
+pub fn tournament(args: &Args) -> Result<()> {
+    let color = args.color();
+    let engines = args.engines();
+    let names: Vec<_> = engines
+        .iter()
+        .enumerate()
+        .map(|(i, e)| format!("{}. {}", i + 1, e.name()))
+        .collect();
+    let dir = args.out_dir().unwrap_or(Path::new("tournament"));
+    fs::create_dir_all(dir)?;
+    let book = Book::load(args.book())?;
+
+    let pairs: Vec<_> = if args.gauntlet() {
+        (1..engines.len()).map(|j| (0, j)).collect()
+    } else {
+        (0..engines.len())
+            .flat_map(|i| (i + 1..engines.len()).map(move |j| (i, j)))
+            .collect()
+    };
+
+    // Points of the engine in the row against the engine in the column.
+    let mut points = vec![vec![0.; engines.len()]; engines.len()];
+    let mut game_num = 0;
+    for (i, j) in pairs {
+        let mut ais = [
+            new_ai(args, engines[i].clone(), i, &book)?,
+            new_ai(args, engines[j].clone(), j, &book)?,
+        ];
+        for game in 0..args.games().unwrap_or(2) {
+            game_num += 1;
+            // The first engine plays cross in the even games.
+            let swap = game % 2 == 1;
+            let [a, b] = &mut ais;
+            let (x, o) = if swap { (b, a) } else { (a, b) };
+            let (xi, oi) = if swap { (j, i) } else { (i, j) };
+
+            let mut board = Board::new(args.position(), args.win_len())?;
+            let (winner, err) = play_game(&mut board, x, o)?;
+            let (xp, op, result) = match winner {
+                Suit::Cross => (1., 0., "1-0"),
+                Suit::Circle => (0., 1., "0-1"),
+                Suit::None => (0.5, 0.5, "1/2-1/2"),
+            };
+            points[xi][oi] += xp;
+            points[oi][xi] += op;
+
+            let mut comments = vec![
+                format!("X: {}", names[xi]),
+                format!("O: {}", names[oi]),
+                format!("Result: {result}"),
+            ];
+            if let Some(e) = &err {
+                comments.push(format!("Forfeit: {e}"));
+            }
+            let file = dir.join(format!(
+                "{game_num:04}-{}-vs-{}.txt",
+                file_name(&engines[xi]),
+                file_name(&engines[oi])
+            ));
+            Game::from_board(&board).save(&file, &comments)?;
+
+            printmcln!(
+                color,
+                "{'gr}{game_num:>4}.{'_} {} {'gr}vs{'_} {} {'w}{result}{'_}",
+                names[xi],
+                names[oi]
+            );
+            if let Some(e) = err {
+                printmcln!(color, "      {'y}forfeit: {'_}{e}");
+            }
+        }
+    }
+
+    let table = crosstable(&names, &points);
+    println!();
+    print!("{table}");
+    fs::write(dir.join(CROSSTABLE_FILE), table)?;
+    printmcln!(color, "\nGames saved to {'i}{}{'_}.", dir.display());
+
+    Ok(())
+}
+
+fn new_ai(args: &Args, kind: AiKind, idx: usize, book: &Book) -> Result<Ai> {
+    let seed = args.seed().map(|s| s.wrapping_add(idx as u64));
+    Ai::new(kind, args.playouts(), args.move_time(), seed, book.clone())
+}
+
+/// Formats the table with points of each engine against each other engine.
+/// Win is 1 point and draw is half point.
+fn crosstable(names: &[String], points: &[Vec<f64>]) -> String {
+    let width = names.iter().map(|n| n.len()).max().unwrap_or_default();
+    let mut res = format!("{:width$}", "");
+    for i in 0..names.len() {
+        _ = write!(res, " {:>5}", i + 1);
+    }
+    res += "  Total\n";
+
+    for (i, name) in names.iter().enumerate() {
+        _ = write!(res, "{name:width$}");
+        for (j, p) in points[i].iter().enumerate() {
+            if i == j {
+                _ = write!(res, " {:>5}", "-");
+            } else {
+                _ = write!(res, " {p:>5.1}");
+            }
+        }
+        let total: f64 = points[i].iter().sum();
+        _ = writeln!(res, "  {total:>5.1}");
+    }
+    res
+}
+
+/// Gets the name of the engine that may be used in file name.
+fn file_name(kind: &AiKind) -> String {
+    kind.name()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -0,0 +1,313 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    board::Board,
+    suit::Suit,
+    threats::{threat_at, Threat},
+    vec2::Vec2,
+};
+
+const DIRS: [(isize, isize); 4] = [(1, 0), (0, 1), (1, 1), (-1, 1)];
+const MAX_VCF_DEPTH: usize = 16;
+const MAX_VCT_DEPTH: usize = 4;
+
+/// Threat space search. Finds forced wins made only by continuous fours
+/// (VCF) or by continuous fours and open threes (VCT) for the player on turn.
+pub struct Tss<'a> {
+    deadline: Instant,
+    stop: Option<&'a AtomicBool>,
+}
+
+impl<'a> Tss<'a> {
+    /// Finds victory by continuous fours for the player on turn. Returns the
+    /// winning line with the moves of both players.
+    pub fn vcf(board: &Board, budget: Duration) -> Option<Vec<Vec2>> {
+        Self::vcf_with_stop(board, budget, None)
+    }
+
+    /// The same as [`Tss::vcf`] but the search also ends when `stop` is
+    /// set.
+    pub fn vcf_with_stop(
+        board: &Board,
+        budget: Duration,
+        stop: Option<&'a AtomicBool>,
+    ) -> Option<Vec<Vec2>> {
+        let mut tss = Self {
+            deadline: Instant::now() + budget,
+            stop,
+        };
+        let mut board = board.clone();
+        tss.search_vcf(&mut board, MAX_VCF_DEPTH)
+    }
+
+    /// Finds victory by continuous threats (fours and open threes) for the
+    /// player on turn. Returns the winning line with the moves of both
+    /// players. The other moves are the main line.
+    ///
+    /// Only some defences against the open threes are checked, so the win
+    /// is likely but not proven unless the line is VCF.
+    pub fn vct(board: &Board, budget: Duration) -> Option<Vec<Vec2>> {
+        Self::vct_with_stop(board, budget, None)
+    }
+
+    /// The same as [`Tss::vct`] but the search also ends when `stop` is
+    /// set.
+    pub fn vct_with_stop(
+        board: &Board,
+        budget: Duration,
+        stop: Option<&'a AtomicBool>,
+    ) -> Option<Vec<Vec2>> {
+        let mut tss = Self {
+            deadline: Instant::now() + budget,
+            stop,
+        };
+        let mut board = board.clone();
+        if let Some(res) = tss.search_vcf(&mut board, MAX_VCF_DEPTH) {
+            return Some(res);
+        }
+        tss.search_vct(&mut board, MAX_VCT_DEPTH)
+    }
+
+    fn timeout(&self) -> bool {
+        Instant::now() > self.deadline
+            || self.stop.is_some_and(|s| s.load(Ordering::Relaxed))
+    }
+
+    fn search_vcf(
+        &mut self,
+        board: &mut Board,
+        depth: usize,
+    ) -> Option<Vec<Vec2>> {
+        let me = board.on_turn();
+        if let Some(&win) = win_cells(board, me).first() {
+            return Some(vec![win]);
+        }
+        if depth == 0 || self.timeout() {
+            return None;
+        }
+
+        let blocks = win_cells(board, me.oposite());
+        if blocks.len() > 1 {
+            return None;
+        }
+
+        for mov in four_moves(board, me) {
+            if !blocks.is_empty() && blocks[0] != mov {
+                continue;
+            }
+            if let Some(mut line) = self
+                .try_four(board, mov, depth, |t, b| t.search_vcf(b, depth - 1))
+            {
+                line.insert(0, mov);
+                return Some(line);
+            }
+        }
+
+        None
+    }
+
+    fn search_vct(
+        &mut self,
+        board: &mut Board,
+        depth: usize,
+    ) -> Option<Vec<Vec2>> {
+        let me = board.on_turn();
+        if let Some(&win) = win_cells(board, me).first() {
+            return Some(vec![win]);
+        }
+        if depth == 0 || self.timeout() {
+            return None;
+        }
+
+        let blocks = win_cells(board, me.oposite());
+        if blocks.len() > 1 {
+            return None;
+        }
+
+        for mov in four_moves(board, me) {
+            if !blocks.is_empty() && blocks[0] != mov {
+                continue;
+            }
+            if let Some(mut line) = self
+                .try_four(board, mov, depth, |t, b| t.search_vct(b, depth - 1))
+            {
+                line.insert(0, mov);
+                return Some(line);
+            }
+        }
+
+        // Open threes give the defender time only if the defender has no
+        // four.
+        if !blocks.is_empty() {
+            return None;
+        }
+
+        for mov in three_moves(board, me) {
+            if let Some(mut line) = self.try_three(board, mov, depth) {
+                line.insert(0, mov);
+                return Some(line);
+            }
+        }
+
+        None
+    }
+
+    /// Plays four at `mov` and lets the defender block it. Then continues
+    /// with `next`.
+    fn try_four(
+        &mut self,
+        board: &mut Board,
+        mov: Vec2,
+        depth: usize,
+        next: impl FnOnce(&mut Self, &mut Board) -> Option<Vec<Vec2>>,
+    ) -> Option<Vec<Vec2>> {
+        let me = board.on_turn();
+        _ = board.play_at(mov);
+        let wins = win_cells(board, me);
+        let res = match wins.len() {
+            0 => None,
+            1 if depth > 1 => {
+                let block = wins[0];
+                _ = board.play_at(block);
+                let res = if board.wins_at(block) {
+                    None
+                } else {
+                    next(self, board).map(|mut l| {
+                        l.insert(0, block);
+                        l
+                    })
+                };
+                board.undo();
+                res
+            }
+            1 => None,
+            // Can't block more than one win
+            _ => win_cells(board, me.oposite())
+                .is_empty()
+                .then(|| vec![wins[0], wins[1]]),
+        };
+        board.undo();
+        res
+    }
+
+    /// Plays open three at `mov` and checks that all the defences lose.
+    fn try_three(
+        &mut self,
+        board: &mut Board,
+        mov: Vec2,
+        depth: usize,
+    ) -> Option<Vec<Vec2>> {
+        _ = board.play_at(mov);
+        let mut line = None;
+        for def in defences(board, mov) {
+            if self.timeout() {
+                line = None;
+                break;
+            }
+            _ = board.play_at(def);
+            let res = if board.wins_at(def) {
+                None
+            } else {
+                self.search_vct(board, depth - 1)
+            };
+            board.undo();
+            match res {
+                Some(mut l) => {
+                    if line.is_none() {
+                        l.insert(0, def);
+                        line = Some(l);
+                    }
+                }
+                None => {
+                    line = None;
+                    break;
+                }
+            }
+        }
+        board.undo();
+        line
+    }
+}
+
+/// Gets the empty cells where `suit` wins by playing there.
+pub fn win_cells(board: &Board, suit: Suit) -> Vec<Vec2> {
+    empty_cells(board)
+        .filter(|p| threat_at(board, *p, suit) == Threat::Win)
+        .collect()
+}
+
+/// Gets the moves that create four (a win threat) for `suit`.
+fn four_moves(board: &mut Board, suit: Suit) -> Vec<Vec2> {
+    let candidates: Vec<_> = empty_cells(board).collect();
+    candidates
+        .into_iter()
+        .filter(|p| {
+            _ = board.play_at(*p);
+            let res = line_cells(board, *p)
+                .any(|c| threat_at(board, c, suit) == Threat::Win);
+            board.undo();
+            res
+        })
+        .collect()
+}
+
+/// Gets the moves that create open line of `win_len - 2` (open three) for
+/// `suit`.
+fn three_moves(board: &mut Board, suit: Suit) -> Vec<Vec2> {
+    if board.win_len() < 4 {
+        return vec![];
+    }
+
+    let candidates: Vec<_> = empty_cells(board).collect();
+    candidates
+        .into_iter()
+        .filter(|p| {
+            _ = board.play_at(*p);
+            let res = line_cells(board, *p)
+                .any(|c| threat_at(board, c, suit) == Threat::OpenFour);
+            board.undo();
+            res
+        })
+        .collect()
+}
+
+/// Gets the moves that the defender may use against threat at `mov`. These
+/// are the empty cells in the lines through `mov` and the cells where the
+/// defender creates four.
+fn defences(board: &mut Board, mov: Vec2) -> Vec<Vec2> {
+    let defender = board.on_turn();
+    let mut res: Vec<_> = line_cells(board, mov).collect();
+    for f in four_moves(board, defender) {
+        if !res.contains(&f) {
+            res.push(f);
+        }
+    }
+    res
+}
+
+/// Gets the empty cells in the lines through `pos` that are close enough to
+/// form line with it.
+fn line_cells(board: &Board, pos: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+    let reach = board.win_len() as isize - 1;
+    DIRS.into_iter()
+        .flat_map(move |(x, y)| {
+            (-reach..=reach).map(move |i| pos.signed() + (x * i, y * i))
+        })
+        .filter(|p| board.contains_signed(*p))
+        .map(|p| p.unsigned())
+        .filter(|p| board[*p].is_none())
+}
+
+/// Gets the empty cells that are near some stone.
+fn empty_cells(board: &Board) -> impl Iterator<Item = Vec2> + '_ {
+    Vec2::new(0, 0).to(board.size()).filter(|p| {
+        board[*p].is_none() && {
+            let start = p.saturating_sub((2, 2));
+            let end = (*p + (3, 3)).cmin(board.size());
+            start.to(end).any(|n| !board[n].is_none())
+        }
+    })
+}
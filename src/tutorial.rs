@@ -0,0 +1,137 @@
+use crate::{board::Board, coord, err::Result, position::Position};
+
+const WIN_LEN: usize = 5;
+
+/// Single step of the tutorial. The player should play one of the answers.
+struct Step {
+    start: &'static str,
+    task: &'static str,
+    answers: &'static [&'static str],
+    /// Shown when the player plays one of the answers.
+    correct: &'static str,
+    /// Shown when the player plays other move.
+    hint: &'static str,
+}
+
+const STEPS: &[Step] = &[
+    Step {
+        start: "x:........./........./........./...o.o.../..xxxx.../\
+            ....o..../........./........./.........",
+        task: "Get five in a row to win. Complete the row of X.",
+        answers: &["B5", "G5"],
+        correct: "Five in a row wins the game.",
+        hint: "Play next to the four X in the same row.",
+    },
+    Step {
+        start: "x:........./....x..../....o..../...xo..../....o.x../\
+            ....o..../........./........./.........",
+        task: "O has four in a row and wins next move. Block it!",
+        answers: &["E3"],
+        correct: "Four must always be blocked (unless you can win first).",
+        hint: "Look at the column of O. Only one end is still open.",
+    },
+    Step {
+        start: "x:........./........./........./...o...../...xxx.../\
+            .....o.../..o....../........./.........",
+        task: "Make open four: four in a row with both ends empty.",
+        answers: &["C5", "G5"],
+        correct: "O can block only one end of open four, so you will win.",
+        hint: "Extend the three of X so that both ends of the four are empty.",
+    },
+    Step {
+        start: "x:........./........./........./......o../...xx.o../\
+            ......o../........./........./.........",
+        task: "O has open three. Block it before it becomes open four.",
+        answers: &["G7", "G3"],
+        correct: "Open three is a threat. Block it next to one of its ends.",
+        hint: "Play right next to one end of the three O.",
+    },
+    Step {
+        start: "x:........./........./........./........./.....x.../\
+            .....x.../.oxxx..../....o..../...o.....",
+        task: "Make four and open three with one move (double threat).",
+        answers: &["F3"],
+        correct: "O can't block both the four and the open three. This is \
+            how most games are won.",
+        hint: "Find the cell that extends both the row and the column of X.",
+    },
+];
+
+/// Guided tutorial with positions where the player has to find the right
+/// move.
+#[derive(Debug, Default)]
+pub struct Tutorial {
+    cur: usize,
+}
+
+impl Tutorial {
+    /// Creates board with the position of the current step.
+    pub fn board(&self) -> Result<Board> {
+        let start = Position::parse(self.step().start)
+            .expect("Tutorial positions are valid.");
+        Board::new(start, WIN_LEN)
+    }
+
+    /// Gets the instructions for the current step.
+    pub fn task(&self) -> String {
+        format!("{}/{}: {}", self.cur + 1, STEPS.len(), self.step().task)
+    }
+
+    /// Checks the last move on the board. Returns the message for the player
+    /// and whether the move was correct.
+    pub fn check(&self, board: &Board) -> Option<(bool, &'static str)> {
+        let last = *board.moves().last()?;
+        let mov = coord::format(last, board.size());
+        let step = self.step();
+        Some(if step.answers.contains(&mov.as_str()) {
+            (true, step.correct)
+        } else {
+            (false, step.hint)
+        })
+    }
+
+    /// Moves to the next step. Returns `false` if this was the last step.
+    pub fn next(&mut self) -> bool {
+        if self.cur + 1 >= STEPS.len() {
+            return false;
+        }
+        self.cur += 1;
+        true
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.cur + 1 == STEPS.len()
+    }
+
+    fn step(&self) -> &'static Step {
+        &STEPS[self.cur]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answers() {
+        let mut tutorial = Tutorial::default();
+        loop {
+            let step = tutorial.step();
+            for answer in step.answers {
+                let mut board = tutorial.board().unwrap();
+                let pos = coord::parse(answer, board.size()).unwrap();
+                board.play_at(pos).unwrap();
+                assert_eq!(
+                    tutorial.check(&board),
+                    Some((true, step.correct)),
+                    "{}",
+                    step.task
+                );
+            }
+            if !tutorial.next() {
+                break;
+            }
+        }
+        assert!(tutorial.is_last());
+    }
+}
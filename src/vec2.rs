@@ -115,10 +115,7 @@ impl<T> Vec2<T> {
 }
 
 impl Vec2<usize> {
-    pub fn wrapping_add_signed(
-        &mut self,
-        other: impl Into<Vec2<isize>>,
-    ) -> Self {
+    pub fn wrapping_add_signed(self, other: impl Into<Vec2<isize>>) -> Self {
         let Vec2 { x, y } = other.into();
         Self {
             x: self.x.wrapping_add_signed(x),
@@ -126,10 +123,7 @@ impl Vec2<usize> {
         }
     }
 
-    pub fn saturating_add_signed(
-        &mut self,
-        other: impl Into<Vec2<isize>>,
-    ) -> Self {
+    pub fn saturating_add_signed(self, other: impl Into<Vec2<isize>>) -> Self {
         let Vec2 { x, y } = other.into();
         Self {
             x: self.x.saturating_add_signed(x),
@@ -0,0 +1,160 @@
+use crate::{
+    rng::{mix, GAMMA},
+    suit::Suit,
+    vec2::Vec2,
+};
+
+/// Incremental Zobrist hash of the cells on board. It keeps the hash of the
+/// board for each of its symmetries (rotations and reflections) so that the
+/// symmetric positions may be identified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Zobrist {
+    /// Hash of the board transformed with each of the symmetries. The first
+    /// one is the hash of the board as it is.
+    hashes: [u64; 8],
+}
+
+impl Zobrist {
+    /// Hashes all the cells of board of the given size.
+    pub fn new(cells: &[Suit], size: Vec2) -> Self {
+        let mut res = Self::default();
+        for pos in Vec2::new(0, 0).to(size) {
+            res.toggle(pos, cells[pos.y * size.x + pos.x], size);
+        }
+        res
+    }
+
+    /// Adds or removes `suit` at `pos`.
+    pub fn toggle(&mut self, pos: Vec2, suit: Suit, size: Vec2) {
+        if suit.is_none() {
+            return;
+        }
+        for (sym, hash) in self.hashes.iter_mut().enumerate() {
+            let p = transform(sym, pos, size);
+            *hash ^= key(p.y * size.x + p.x, suit);
+        }
+    }
+
+    /// Gets the hash of the board as it is.
+    pub fn hash(&self) -> u64 {
+        self.hashes[0]
+    }
+
+    /// Gets hash that is the same for all the positions that are rotations
+    /// or reflections of each other.
+    pub fn canonical(&self, size: Vec2) -> u64 {
+        self.hashes[..symmetries(size)]
+            .iter()
+            .copied()
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+/// Gets the key of the player on turn.
+pub fn turn_key(suit: Suit) -> u64 {
+    match suit {
+        Suit::Circle => mix(GAMMA.wrapping_neg()),
+        _ => 0,
+    }
+}
+
+/// Gets the number of symmetries of board with the given size. Only square
+/// boards may be rotated by 90 degrees.
+pub fn symmetries(size: Vec2) -> usize {
+    if size.x == size.y {
+        8
+    } else {
+        4
+    }
+}
+
+/// Applies the inverse of the symmetry `sym` to `pos`.
+pub fn untransform(sym: usize, pos: Vec2, size: Vec2) -> Vec2 {
+    // Only the rotations by 90 degrees are not their own inverse.
+    let inv = match sym {
+        5 => 6,
+        6 => 5,
+        s => s,
+    };
+    transform(inv, pos, size)
+}
+
+/// Applies the symmetry `sym` to `pos`. Symmetries `4..8` transpose the board
+/// and so they are valid only for square boards.
+pub fn transform(sym: usize, pos: Vec2, size: Vec2) -> Vec2 {
+    let Vec2 { x, y } = pos;
+    let (mx, my) = (size.x - 1 - x, size.y - 1 - y);
+    match sym {
+        0 => (x, y),
+        1 => (mx, y),
+        2 => (x, my),
+        3 => (mx, my),
+        // Transposition is valid only on square board. It would overflow on
+        // the other boards, so the position is left as it is.
+        _ if size.x != size.y => (x, y),
+        4 => (y, x),
+        5 => (my, x),
+        6 => (y, mx),
+        _ => (my, mx),
+    }
+    .into()
+}
+
+/// Gets the random key of `suit` at the cell with index `idx`. The keys are
+/// computed so that they don't need to be stored.
+fn key(idx: usize, suit: Suit) -> u64 {
+    let s = if suit == Suit::Cross { 0 } else { 1 };
+    mix((idx as u64 * 2 + s + 1).wrapping_mul(GAMMA))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_untransform() {
+        for size in [Vec2::new(5, 5), Vec2::new(6, 4)] {
+            for sym in 0..symmetries(size) {
+                for pos in Vec2::new(0, 0).to(size) {
+                    let p = transform(sym, pos, size);
+                    assert!(p.x < size.x && p.y < size.y);
+                    assert_eq!(untransform(sym, p, size), pos, "{sym}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical() {
+        let size = Vec2::new(5, 5);
+        let mut cells = vec![Suit::None; size.prod()];
+        cells[1] = Suit::Cross;
+        cells[7] = Suit::Circle;
+        let hash = Zobrist::new(&cells, size);
+
+        for sym in 0..symmetries(size) {
+            let mut other = vec![Suit::None; size.prod()];
+            for pos in Vec2::new(0, 0).to(size) {
+                let p = transform(sym, pos, size);
+                other[p.y * size.x + p.x] = cells[pos.y * size.x + pos.x];
+            }
+            let other = Zobrist::new(&other, size);
+            assert_eq!(other.canonical(size), hash.canonical(size));
+        }
+    }
+
+    #[test]
+    fn test_toggle() {
+        let size = Vec2::new(4, 3);
+        let mut hash = Zobrist::new(&[Suit::None; 12], size);
+        let empty = hash;
+        hash.toggle((2, 1).into(), Suit::Cross, size);
+        assert_ne!(hash, empty);
+        let mut cells = [Suit::None; 12];
+        cells[6] = Suit::Cross;
+        assert_eq!(hash, Zobrist::new(&cells, size));
+        hash.toggle((2, 1).into(), Suit::Cross, size);
+        assert_eq!(hash, empty);
+    }
+}